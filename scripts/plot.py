@@ -1,18 +1,17 @@
 import sys
-import re
+import json
 import matplotlib.pyplot as plt
 from collections import deque
 
-def extract_info(log_line):
-    pattern = r"Generation (\d+) over\. Survivors \d+/\d+ \(([\d\.]+)%\)"
-    match = re.search(pattern, log_line)
-    
-    if match:
-        generation_number = int(match.group(1))  # Group 1 captures the generation number
-        survival_rate = float(match.group(2))    # Group 2 captures the survival rate percentage
-        return generation_number, survival_rate
-    else:
-        raise ValueError("String does not match the expected pattern")
+def extract_info(record_line):
+    # Expects stats written with `--stats-format jsonl`. Anything else on stdout is skipped.
+    if not record_line.startswith("{"):
+        return None
+
+    record = json.loads(record_line)
+    generation_number = record["generation"]
+    survival_rate = record["survivors"] / record["population"] * 100.0
+    return generation_number, survival_rate
 
 
 def main():
@@ -34,8 +33,13 @@ def main():
     trend_data = deque(maxlen=trend_window_size)
 
     for line in sys.stdin:
-        print(line)
-        generation_number, survival_rate = extract_info(line)
+        print(line, end="")
+        info = extract_info(line)
+
+        if info is None:
+            continue
+
+        generation_number, survival_rate = info
 
         if len(trend_data) >= trend_window_size:
             running_sum -= trend_data[0]
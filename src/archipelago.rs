@@ -0,0 +1,172 @@
+use cellular_automata::automata::Automata;
+use rand::seq::index::sample;
+use serde::Deserialize;
+
+use crate::{
+    headless::Progress,
+    life_sim::{EntityColors, LifeSim},
+    rng::SimRng,
+    services::stats::StatsWriter,
+    stats::GenerationStats,
+};
+
+/// How populations are exchanged between islands.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Migration {
+    /// Migrants move after every this many generations.
+    pub interval: u32,
+    /// How many entities each island sends to the next one along.
+    pub migrants: u32,
+}
+
+/// Several populations evolving side by side, each in its own `LifeSim`, that exchange migrants
+/// every few generations. Islands advance in lockstep a generation at a time, so islands with
+/// shorter generations wait for the rest before starting the next one.
+pub struct Archipelago {
+    islands: Vec<LifeSim>,
+    migration: Migration,
+    /// Generations every island has finished.
+    generation_number: u32,
+    generation_limit: Option<u32>,
+    stats_writer: StatsWriter,
+    rng: SimRng,
+}
+
+impl Archipelago {
+    pub fn new(
+        islands: Vec<LifeSim>,
+        migration: Migration,
+        generation_limit: Option<u32>,
+        stats_writer: StatsWriter,
+        rng: SimRng,
+    ) -> Self {
+        Self {
+            islands,
+            migration,
+            generation_number: 0,
+            generation_limit,
+            stats_writer,
+            rng,
+        }
+    }
+
+    /// Moves migrants around the ring of islands: each island sends entities picked at random to
+    /// the next island, where they take the place of the same number of entities picked at random
+    /// and keep their species where the island has it.
+    fn migrate(&mut self) {
+        if self.islands.len() < 2 {
+            return;
+        }
+
+        let count = self
+            .islands
+            .iter()
+            .map(|island| island.population())
+            .min()
+            .unwrap_or(0)
+            .min(self.migration.migrants as usize);
+
+        let slots: Vec<Vec<usize>> = self
+            .islands
+            .iter()
+            .map(|island| sample(&mut self.rng, island.population(), count).into_vec())
+            .collect();
+
+        let mut emigrants: Vec<_> = self
+            .islands
+            .iter()
+            .zip(&slots)
+            .map(|(island, slots)| island.genomes_at(slots))
+            .collect();
+
+        // Island `i` receives the emigrants of island `i - 1`, and the first the last's.
+        emigrants.rotate_right(1);
+
+        for ((island, slots), genomes) in self.islands.iter_mut().zip(&slots).zip(emigrants) {
+            island.implant_genomes(slots, genomes);
+        }
+    }
+}
+
+impl Progress for Archipelago {
+    fn is_finished(&self) -> bool {
+        self.generation_limit
+            .is_some_and(|limit| self.generation_number >= limit)
+    }
+
+    fn generation_number(&self) -> u32 {
+        self.generation_number
+    }
+
+    fn latest_stats(&self) -> Vec<&GenerationStats> {
+        self.islands
+            .iter()
+            .filter_map(|island| island.last_generation_stats())
+            .collect()
+    }
+}
+
+impl Automata<EntityColors> for Archipelago {
+    fn update(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
+        for island in &mut self.islands {
+            if island.generation_number() == self.generation_number {
+                island.update();
+            }
+        }
+
+        if self
+            .islands
+            .iter()
+            .any(|island| island.generation_number() == self.generation_number)
+        {
+            return;
+        }
+
+        self.generation_number += 1;
+
+        // Written here rather than by each island, so they come out in island order.
+        for stats in self
+            .islands
+            .iter()
+            .filter_map(|island| island.last_generation_stats())
+        {
+            if let Err(err) = self.stats_writer.write(stats) {
+                eprintln!("Warning: could not write stats: {}", err);
+            }
+        }
+
+        if self
+            .generation_number
+            .is_multiple_of(self.migration.interval)
+            && !self.is_finished()
+        {
+            self.migrate();
+        }
+    }
+
+    // Only the first island is shown.
+    fn before_render(&self) -> EntityColors {
+        self.islands[0].before_render()
+    }
+
+    fn render(&self, entity_colors: &EntityColors, i: usize, pixel: &mut [u8]) {
+        self.islands[0].render(entity_colors, i, pixel);
+    }
+
+    fn grid_width(&self) -> u32 {
+        self.islands[0].grid_width()
+    }
+
+    fn grid_height(&self) -> u32 {
+        self.islands[0].grid_height()
+    }
+
+    fn render_pixel_scale(&self) -> u32 {
+        self.islands[0].render_pixel_scale()
+    }
+}
@@ -1,12 +1,29 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
 use crate::grid_config::GridConfig;
 
 use super::neural_network::output_neuron::OutputNeuron;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DeathCause {
+    Radiation,
+    Starvation,
+    Predation,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Body {
     pub x: u32,
     pub y: u32,
-    pub is_alive: bool, // currently used only for radiation.
+    pub is_alive: bool,
+    pub death_cause: Option<DeathCause>,
+    /// Only spent and refilled in scenarios with an `energy` section.
+    pub energy: f32,
+    /// Whether the last move this body tried was blocked by another entity.
+    pub blocked: bool,
+    /// The direction this body last tried to move in.
+    pub facing: (i32, i32),
     pub color_gradient_index: f64,
 }
 
@@ -16,60 +33,46 @@ impl Body {
             x,
             y,
             is_alive: true,
+            death_cause: None,
+            energy: 0.0,
+            blocked: false,
+            facing: (1, 0),
             color_gradient_index,
         }
     }
 
-    pub fn update(&mut self, decision: OutputNeuron, grid_config: &GridConfig) {
-        match decision {
-            OutputNeuron::Stay => {}
-            OutputNeuron::MoveLeft => {
-                if self.x > 0 {
-                    self.x -= 1;
-                }
-            }
-            OutputNeuron::MoveRight => {
-                if self.x < grid_config.width - 1 {
-                    self.x += 1;
-                }
-            }
-            OutputNeuron::MoveUp => {
-                if self.y > 0 {
-                    self.y -= 1;
-                }
-            }
-            OutputNeuron::MoveDown => {
-                if self.y < grid_config.height - 1 {
-                    self.y += 1;
-                }
-            }
-            OutputNeuron::MoveRandom => {
-                let direction = rand::random::<u8>() % 4;
+    pub fn die(&mut self, cause: DeathCause) {
+        self.is_alive = false;
+        self.death_cause = Some(cause);
+    }
+
+    /// The cell this body would end up on after carrying out `decision`. Moves off the edge of
+    /// the grid leave it where it is.
+    pub fn next_position(
+        &self,
+        decision: OutputNeuron,
+        grid_config: &GridConfig,
+        rng: &mut impl Rng,
+    ) -> (u32, u32) {
+        let (x, y) = (self.x, self.y);
 
-                match direction {
-                    0 => {
-                        if self.x > 0 {
-                            self.x -= 1;
-                        }
-                    }
-                    1 => {
-                        if self.x < grid_config.width - 1 {
-                            self.x += 1;
-                        }
-                    }
-                    2 => {
-                        if self.y > 0 {
-                            self.y -= 1;
-                        }
-                    }
-                    3 => {
-                        if self.y < grid_config.height - 1 {
-                            self.y += 1;
-                        }
-                    }
-                    _ => unreachable!(),
-                }
-            }
+        let decision = match decision {
+            OutputNeuron::MoveRandom => match rng.gen::<u8>() % 4 {
+                0 => OutputNeuron::MoveLeft,
+                1 => OutputNeuron::MoveRight,
+                2 => OutputNeuron::MoveUp,
+                3 => OutputNeuron::MoveDown,
+                _ => unreachable!(),
+            },
+            decision => decision,
+        };
+
+        match decision {
+            OutputNeuron::MoveLeft if x > 0 => (x - 1, y),
+            OutputNeuron::MoveRight if x < grid_config.width - 1 => (x + 1, y),
+            OutputNeuron::MoveUp if y > 0 => (x, y - 1),
+            OutputNeuron::MoveDown if y < grid_config.height - 1 => (x, y + 1),
+            _ => (x, y),
         }
     }
 }
@@ -80,6 +83,10 @@ impl Clone for Body {
             x: self.x,
             y: self.y,
             is_alive: self.is_alive,
+            death_cause: self.death_cause,
+            energy: self.energy,
+            blocked: self.blocked,
+            facing: self.facing,
             color_gradient_index: self.color_gradient_index,
         }
     }
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::services::{genomes::GenomeFormat, stats::StatsFormat};
+
+#[derive(Debug, Parser)]
+#[command(about = "Simulates biological life and evolution in cellular automata.")]
+pub struct Cli {
+    /// Scenario name (looked up in ./data/scenarios) or path to a scenario JSON file.
+    #[arg(default_value = "wave")]
+    pub scenario: String,
+
+    /// Train through a curriculum of scenarios (a name in ./data/curricula or a path to a
+    /// curriculum JSON file) instead of a single scenario.
+    #[arg(long, value_name = "CURRICULUM", conflicts_with = "resume")]
+    pub curriculum: Option<String>,
+
+    /// Evolve several populations side by side, as described by an islands file (a name in
+    /// ./data/islands or a path), instead of a single one.
+    #[arg(long, value_name = "ISLANDS", conflicts_with_all = ["resume", "curriculum", "checkpoint_interval"])]
+    pub islands: Option<String>,
+
+    /// Settings files layered on top of default.toml, in the order given.
+    #[arg(short, long = "settings", value_name = "PATH")]
+    pub settings: Vec<PathBuf>,
+
+    /// Stop once this many generations have run, counting those before a resumed checkpoint. Runs
+    /// until closed when omitted.
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub generations: Option<u32>,
+
+    /// Directory that generated files (dot graphs, etc.) are written to.
+    #[arg(short, long, default_value = "./data", value_name = "DIR")]
+    pub output_dir: PathBuf,
+
+    /// Seed for the random number generator. A random seed is chosen (and printed) when omitted.
+    #[arg(long, conflicts_with = "resume")]
+    pub seed: Option<u64>,
+
+    /// Resume from a checkpoint file instead of starting a new population. The scenario stored in
+    /// the checkpoint is used.
+    #[arg(long, value_name = "PATH")]
+    pub resume: Option<PathBuf>,
+
+    /// Write a checkpoint to <OUTPUT_DIR>/checkpoints every N generations.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub checkpoint_interval: Option<u32>,
+
+    /// Found the first generation from a genome file (a name in ./data/genomes or a path) instead
+    /// of random genomes.
+    #[arg(long, value_name = "GENOMES", conflicts_with = "resume")]
+    pub founders: Option<String>,
+
+    /// After every generation, save this many of the highest-scoring breeders to
+    /// <OUTPUT_DIR>/champions/latest.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub champions: Option<u32>,
+
+    /// File format used when saving champions.
+    #[arg(long, value_enum, default_value = "json")]
+    pub champion_format: GenomeFormat,
+
+    /// Format of the per-generation stats records.
+    #[arg(long, value_enum, default_value = "text")]
+    pub stats_format: StatsFormat,
+
+    /// Write per-generation stats to this file instead of stdout.
+    #[arg(long, value_name = "PATH")]
+    pub stats_file: Option<PathBuf>,
+
+    /// Number of threads used to update entities. Defaults to one per CPU core.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Run without opening a window. Needs `--generations`, as nothing else ends the run.
+    #[arg(long, requires = "generations")]
+    pub headless: bool,
+}
@@ -1,124 +1,297 @@
 use cellular_automata::grid::grid_coords_to_index;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     body::Body,
     entity_config::EntityConfig,
-    genome::{mutation::mutate_genome, random_genome},
+    genome::{crossover::crossover_genomes, gene::Gene, mutation::mutate_genome, random_genome},
     grid_config::GridConfig,
     neural_network::brain::Brain,
     neural_network_config::NeuralNetworkConfig,
+    rng::{derive_rng, SimRng},
+    scenario::scenario::Scenario,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Entity {
     pub brain: Brain,
     pub body: Body,
+    /// Index of the entity's species in the scenario. Always 0 in scenarios without species.
+    pub species: usize,
     pub times_eaten: u32,
     pub times_irradiated: u32,
+    /// Radiation absorbed and not yet recovered from.
+    pub radiation_dose: f32,
+    /// Score lost to radiation so far.
+    pub radiation_penalty: f32,
+    pub distance_travelled: u32,
+    pub steps_alive: u32,
+    /// How many of the scenario's survival checkpoints have been passed through, in order.
+    pub checkpoints_reached: u32,
+    /// Entities of other species hunted down.
+    pub kills: u32,
+    /// Score carried over from earlier phases of the generation, which had their own fitness.
+    pub score_offset: f32,
+    pub rng: SimRng,
 }
 
+/// The cells entities can still be spawned on, which are those that aren't walls or already taken
+/// by another entity of the same generation.
+pub struct SpawnCells {
+    width: u32,
+    height: u32,
+    taken: Vec<bool>,
+    free: usize,
+}
+
+impl SpawnCells {
+    pub fn new(grid_config: &GridConfig, scenario: &Scenario) -> Self {
+        let (width, height) = (grid_config.width, grid_config.height);
+        let taken: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|cell| scenario.is_wall(cell))
+            .collect();
+        let free = taken.iter().filter(|taken| !**taken).count();
+
+        Self {
+            width,
+            height,
+            taken,
+            free,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.free == 0
+    }
+
+    /// Takes a random free cell, or returns `None` once there are none left.
+    fn take_random(&mut self, rng: &mut impl Rng) -> Option<(u32, u32)> {
+        if self.is_full() {
+            return None;
+        }
+
+        loop {
+            let x = rng.gen::<u32>() % self.width;
+            let y = rng.gen::<u32>() % self.height;
+
+            let index = grid_coords_to_index(x, y, self.width);
+
+            if !self.taken[index] {
+                self.taken[index] = true;
+                self.free -= 1;
+                return Some((x, y));
+            }
+        }
+    }
+}
+
+/// Spawns an entity on a random free cell, or returns `None` if the grid is full.
 fn spawn_entity(
     brain: Brain,
-    occupied_positions: &mut Vec<usize>,
-    grid_config: &GridConfig,
-) -> Entity {
-    let (x, y) = get_random_position(occupied_positions, grid_config.width, grid_config.height);
-    let body = Body::new(x, y, rand::random::<f64>());
+    species: usize,
+    cells: &mut SpawnCells,
+    rng: &mut impl Rng,
+) -> Option<Entity> {
+    let (x, y) = cells.take_random(rng)?;
+    let body = Body::new(x, y, rng.gen::<f64>());
 
-    Entity {
+    Some(Entity {
         brain,
         body,
+        species,
         times_eaten: 0,
         times_irradiated: 0,
-    }
+        radiation_dose: 0.0,
+        radiation_penalty: 0.0,
+        distance_travelled: 0,
+        steps_alive: 0,
+        checkpoints_reached: 0,
+        kills: 0,
+        score_offset: 0.0,
+        rng: derive_rng(rng),
+    })
 }
 
-type SpawnedEntities = (Vec<Entity>, Vec<usize>);
+type SpawnedEntities = (Vec<Entity>, SpawnCells);
 
+/// Spawns up to `num_entities` entities with random genomes, stopping early if the grid fills up.
 pub fn spawn_entities(
     grid_config: &GridConfig,
+    scenario: &Scenario,
     network_config: &NeuralNetworkConfig,
+    species: usize,
     num_entities: u32,
     existing_entities: Option<SpawnedEntities>,
+    rng: &mut impl Rng,
 ) -> SpawnedEntities {
-    let (mut entities, mut used_positions) = existing_entities.unwrap_or((Vec::new(), Vec::new()));
+    let (mut entities, mut cells) =
+        existing_entities.unwrap_or_else(|| (Vec::new(), SpawnCells::new(grid_config, scenario)));
 
     for _ in 0..num_entities {
-        let genome = random_genome(network_config);
-        let entity = spawn_entity(Brain::from_genome(genome), &mut used_positions, grid_config);
+        if cells.is_full() {
+            break;
+        }
 
-        entities.push(entity);
+        let genome = random_genome(network_config, rng);
+
+        if let Some(entity) = spawn_entity(Brain::from_genome(genome), species, &mut cells, rng) {
+            entities.push(entity);
+        }
     }
 
-    (entities, used_positions)
+    (entities, cells)
 }
 
+/// Spawns a population of random genomes, split between the scenario's species.
+pub fn spawn_population(
+    grid_config: &GridConfig,
+    scenario: &Scenario,
+    network_config: &NeuralNetworkConfig,
+    num_entities: u32,
+    rng: &mut impl Rng,
+) -> Vec<Entity> {
+    let mut spawned = (Vec::new(), SpawnCells::new(grid_config, scenario));
+
+    for (species, count) in scenario
+        .species_counts(num_entities)
+        .into_iter()
+        .enumerate()
+    {
+        spawned = spawn_entities(
+            grid_config,
+            scenario,
+            network_config,
+            species,
+            count,
+            Some(spawned),
+            rng,
+        );
+    }
+
+    spawned.0
+}
+
+/// Spawns a population from saved genomes, cycling through them until `num_entities` are placed.
+/// Species are filled one after another.
+pub fn spawn_founders(
+    grid_config: &GridConfig,
+    scenario: &Scenario,
+    founders: &[Vec<Gene>],
+    num_entities: u32,
+    rng: &mut impl Rng,
+) -> Vec<Entity> {
+    let mut cells = SpawnCells::new(grid_config, scenario);
+    let species = scenario
+        .species_counts(num_entities)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(species, count)| std::iter::repeat_n(species, count as usize));
+
+    founders
+        .iter()
+        .cycle()
+        .zip(species)
+        .map_while(|(genome, species)| {
+            spawn_entity(Brain::from_genome(genome.clone()), species, &mut cells, rng)
+        })
+        .collect()
+}
+
+/// Breeds the next generation from `selected`. Each species breeds only within itself, and is
+/// topped back up to its share of the population separately. Breeding stops once every free cell
+/// of the grid is taken.
 pub fn spawn_next_generation(
     grid_config: &GridConfig,
     entity_config: &EntityConfig,
     network_config: &NeuralNetworkConfig,
-    supplement_population: bool,
-    limit_population: bool,
-    mut selected: Vec<Entity>,
-) -> Vec<Entity> {
-    if limit_population {
-        let max_population =
-            (entity_config.start_count / entity_config.survivor_child_count) as usize;
-        selected.sort_by(|a, b| b.times_eaten.cmp(&a.times_eaten));
-        selected.truncate(max_population);
+    scenario: &Scenario,
+    selected: Vec<Entity>,
+    rng: &mut impl Rng,
+) -> (Vec<Entity>, u32) {
+    let species_counts = scenario.species_counts(entity_config.start_count);
+    let mut pools: Vec<Vec<Entity>> = species_counts.iter().map(|_| Vec::new()).collect();
+
+    for entity in selected {
+        pools[scenario.species_of(&entity)].push(entity);
     }
 
     let mut next_generation = Vec::<Entity>::new();
-    let mut used_positions = Vec::<usize>::new();
+    let mut cells = SpawnCells::new(grid_config, scenario);
+    let mut mutations = 0;
 
-    // Create children for each selected entity.
-    for Entity { brain, .. } in selected {
-        for _ in 0..entity_config.survivor_child_count {
-            let mut genome = brain.genome.clone();
+    for (species, (mut selected, species_count)) in
+        pools.into_iter().zip(species_counts).enumerate()
+    {
+        if scenario.limit_population {
+            let max_population = (species_count / entity_config.survivor_child_count) as usize;
+            selected.sort_by(|a, b| b.times_eaten.cmp(&a.times_eaten));
+            selected.truncate(max_population);
+        }
 
-            let roll = rand::random::<f32>();
+        let species_start = next_generation.len();
 
-            if roll < network_config.mutation_rate {
-                mutate_genome(&mut genome);
-            }
+        // Create children for each selected entity.
+        'breeding: for (i, Entity { brain, .. }) in selected.iter().enumerate() {
+            for _ in 0..entity_config.survivor_child_count {
+                if cells.is_full() {
+                    break 'breeding;
+                }
 
-            let brain = Brain::from_genome(genome);
+                let mut genome = match scenario.crossover {
+                    // Each child gets a partner picked at random from the other selected entities.
+                    Some(strategy) if selected.len() > 1 => {
+                        let partner = (i + rng.gen_range(1..selected.len())) % selected.len();
+                        crossover_genomes(
+                            &brain.genome,
+                            &selected[partner].brain.genome,
+                            strategy,
+                            rng,
+                        )
+                    }
+                    _ => brain.genome.clone(),
+                };
 
-            let entity = spawn_entity(brain, &mut used_positions, grid_config);
-            next_generation.push(entity);
-        }
-    }
+                let roll = rng.gen::<f32>();
 
-    // Generate new entities to fill the remaining slots.
-    let num_remaining = entity_config.start_count as i32 - next_generation.len() as i32;
+                if roll < network_config.mutation_rate {
+                    mutate_genome(&mut genome, rng);
+                    mutations += 1;
+                }
 
-    if !supplement_population || num_remaining <= 0 {
-        return next_generation;
-    }
+                let brain = Brain::from_genome(genome);
+
+                next_generation.extend(spawn_entity(brain, species, &mut cells, rng));
+            }
+        }
 
-    let (next_generation, _) = spawn_entities(
-        grid_config,
-        network_config,
-        num_remaining as u32,
-        Some((next_generation, used_positions)),
-    );
+        // Generate new entities to fill the remaining slots.
+        let num_remaining = species_count as i32 - (next_generation.len() - species_start) as i32;
 
-    next_generation
-}
+        if !scenario.supplement_population || num_remaining <= 0 {
+            continue;
+        }
 
-fn get_random_position(
-    occupied_positions: &Vec<usize>,
-    grid_width: u32,
-    grid_height: u32,
-) -> (u32, u32) {
-    loop {
-        let x = rand::random::<u32>() % grid_width;
-        let y = rand::random::<u32>() % grid_height;
+        (next_generation, cells) = spawn_entities(
+            grid_config,
+            scenario,
+            network_config,
+            species,
+            num_remaining as u32,
+            Some((next_generation, cells)),
+            rng,
+        );
+    }
 
-        let index = grid_coords_to_index(x, y, grid_width);
+    (next_generation, mutations)
+}
 
-        if !occupied_positions.contains(&index) {
-            return (x, y);
+/// Fills up every entity's energy for the start of a generation, if the scenario uses energy.
+pub fn give_starting_energy(entities: &mut [Entity], scenario: &Scenario) {
+    if let Some(energy) = &scenario.energy {
+        for entity in entities {
+            entity.body.energy = energy.starting;
         }
     }
 }
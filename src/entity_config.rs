@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct EntityConfig {
     pub start_count: u32,
     pub survivor_child_count: u32,
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::gene::Gene;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CrossoverStrategy {
+    /// Genes before a random cut point come from the first parent, the rest from the second.
+    SinglePoint,
+    /// Each gene position is taken from either parent at random.
+    Uniform,
+    /// Genes describing the same connection in both parents are inherited from either at random.
+    /// Connections only the first parent has are kept, those only the second has are dropped.
+    GeneAligned,
+}
+
+type ConnectionKey = (bool, u16, u16, bool, u16, u16);
+
+fn connection_key(gene: &Gene) -> ConnectionKey {
+    (
+        gene.source_is_hidden,
+        gene.source_discriminant,
+        gene.source_instance,
+        gene.target_is_output,
+        gene.target_discriminant,
+        gene.target_instance,
+    )
+}
+
+pub fn crossover_genomes(
+    first: &[Gene],
+    second: &[Gene],
+    strategy: CrossoverStrategy,
+    rng: &mut impl Rng,
+) -> Vec<Gene> {
+    match strategy {
+        CrossoverStrategy::SinglePoint => {
+            let cut = rng.gen_range(0..=first.len().min(second.len()));
+
+            first[..cut]
+                .iter()
+                .chain(second[cut..].iter())
+                .cloned()
+                .collect()
+        }
+        CrossoverStrategy::Uniform => first
+            .iter()
+            .enumerate()
+            .map(|(i, gene)| match second.get(i) {
+                Some(other) if rng.gen::<bool>() => other.clone(),
+                _ => gene.clone(),
+            })
+            .collect(),
+        CrossoverStrategy::GeneAligned => {
+            let second_genes: HashMap<ConnectionKey, &Gene> = second
+                .iter()
+                .map(|gene| (connection_key(gene), gene))
+                .collect();
+
+            first
+                .iter()
+                .map(|gene| match second_genes.get(&connection_key(gene)) {
+                    Some(other) if rng.gen::<bool>() => (*other).clone(),
+                    _ => gene.clone(),
+                })
+                .collect()
+        }
+    }
+}
@@ -1,10 +1,11 @@
+pub mod crossover;
 pub mod gene;
 pub mod mutation;
 
 use std::collections::HashMap;
 
 use gene::Gene;
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 use strum::IntoEnumIterator;
 
 use crate::{
@@ -14,7 +15,7 @@ use crate::{
     neural_network_config::NeuralNetworkConfig,
 };
 
-pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
+pub fn random_genome(network_config: &NeuralNetworkConfig, rng: &mut impl Rng) -> Vec<Gene> {
     let NeuralNetworkConfig {
         hidden_layer_width,
         hidden_layer_depth,
@@ -22,8 +23,6 @@ pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
     } = network_config;
     let mut genome = Vec::new();
 
-    let mut rng = rand::thread_rng();
-
     let mut input_layer = Vec::new();
 
     let mut hidden_discriminant_instances = HashMap::<u16, u16>::new();
@@ -31,16 +30,12 @@ pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
 
     // Create random connections from input to hidden.
     for _ in 0..*hidden_layer_width {
-        let input_discriminant =
-            InputNeuron::iter().choose(&mut rng).unwrap().discriminant() as u16;
+        let input_discriminant = InputNeuron::iter().choose(rng).unwrap().discriminant() as u16;
 
-        let hidden_discriminant = HiddenNeuron::iter()
-            .choose(&mut rng)
-            .unwrap()
-            .discriminant() as u16;
+        let hidden_discriminant = HiddenNeuron::iter().choose(rng).unwrap().discriminant() as u16;
 
         // between -1.0 and 1.0.
-        let weight = (rand::random::<f32>() - 0.5) * 2.0;
+        let weight = (rng.gen::<f32>() - 0.5) * 2.0;
 
         let target_instance = *hidden_discriminant_instances
             .entry(hidden_discriminant)
@@ -68,14 +63,11 @@ pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
         let mut hidden_layer = Vec::new();
 
         for (source_discriminant, source_instance) in prev_layer {
-            let mut rng = rand::thread_rng();
-            let hidden_discriminant = HiddenNeuron::iter()
-                .choose(&mut rng)
-                .unwrap()
-                .discriminant() as u16;
+            let hidden_discriminant =
+                HiddenNeuron::iter().choose(rng).unwrap().discriminant() as u16;
 
             // between -1.0 and 1.0.
-            let weight = (rand::random::<f32>() - 0.5) * 2.0;
+            let weight = (rng.gen::<f32>() - 0.5) * 2.0;
 
             let target_instance = *hidden_discriminant_instances
                 .entry(hidden_discriminant)
@@ -101,13 +93,10 @@ pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
 
     // Create random connections from hidden to output.
     for (source_discriminant, source_instance) in prev_layer {
-        let output_discriminant = OutputNeuron::iter()
-            .choose(&mut rand::thread_rng())
-            .unwrap()
-            .discriminant() as u16;
+        let output_discriminant = OutputNeuron::iter().choose(rng).unwrap().discriminant() as u16;
 
         // between -1.0 and 1.0.
-        let weight = (rand::random::<f32>() - 0.5) * 2.0;
+        let weight = (rng.gen::<f32>() - 0.5) * 2.0;
 
         let target_instance = *output_discriminant_instances
             .entry(output_discriminant)
@@ -129,3 +118,11 @@ pub fn random_genome(network_config: &NeuralNetworkConfig) -> Vec<Gene> {
 
     genome
 }
+
+/// The fraction of genes two genomes have in common, position by position. Genomes of the same
+/// lineage line up, so this falls as mutations build up between relatives.
+pub fn genome_similarity(first: &[Gene], second: &[Gene]) -> f32 {
+    let shared = first.iter().zip(second).filter(|(a, b)| a == b).count();
+
+    shared as f32 / first.len().max(second.len()).max(1) as f32
+}
@@ -2,9 +2,7 @@ use rand::Rng;
 
 use super::gene::Gene;
 
-pub fn mutate_genome(genome: &mut Vec<Gene>) {
-    let mut rng = rand::thread_rng();
-
+pub fn mutate_genome(genome: &mut [Gene], rng: &mut impl Rng) {
     let element_index = rng.gen_range(0..genome.len());
     let bit_index16 = 1 << rng.gen_range(0..16);
 
@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use cellular_automata::automata::Automata;
+
+use crate::stats::GenerationStats;
+
+/// How far a simulation has got, for running it without a window.
+pub trait Progress {
+    fn is_finished(&self) -> bool;
+    fn generation_number(&self) -> u32;
+    /// The stats of the last generation to finish, one for each population.
+    fn latest_stats(&self) -> Vec<&GenerationStats>;
+}
+
+pub fn run_headless<T>(mut sim: impl Automata<T> + Progress) {
+    let start = Instant::now();
+    let mut steps: u64 = 0;
+
+    while !sim.is_finished() {
+        sim.update();
+        steps += 1;
+    }
+
+    let elapsed = start.elapsed().as_secs_f32();
+
+    // The summary goes to stderr so it doesn't end up mixed in with stats written to stdout.
+    eprintln!(
+        "Headless run finished. {} generations, {} steps in {:.2}s ({:.0} steps/s)",
+        sim.generation_number(),
+        steps,
+        elapsed,
+        steps as f32 / elapsed.max(f32::EPSILON)
+    );
+
+    for stats in sim.latest_stats() {
+        let island = stats
+            .island
+            .map(|island| format!("Island {}: ", island))
+            .unwrap_or_default();
+
+        eprintln!(
+            "{}Final generation {}: survivors {}/{}, breeders {}/{} ({:.2}%)",
+            island,
+            stats.generation,
+            stats.survivors,
+            stats.population,
+            stats.breeders,
+            stats.population,
+            stats.breeders as f32 / stats.population as f32 * 100.0
+        );
+    }
+}
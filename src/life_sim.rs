@@ -4,29 +4,63 @@ use cellular_automata::{
     automata::Automata,
     viewport::{viewport_index_to_coords, viewport_to_grid},
 };
+use rayon::prelude::*;
 
 use crate::{
-    entity::{spawn_entities, spawn_next_generation, Entity},
+    body::DeathCause,
+    entity::{
+        give_starting_energy, spawn_founders, spawn_next_generation, spawn_population, Entity,
+    },
+    genome::gene::Gene,
+    headless::Progress,
+    neighbours::{sense_neighbours, NeighbourSenses},
+    neural_network::brain::Brain,
+    neural_network::{output_neuron::OutputNeuron, senses::Senses},
     neural_network_config::NeuralNetworkConfig,
+    occupancy::OccupancyGrid,
     rendering::additive_blend,
-    selection::select_breeders,
-    services::dot::write_dot_file,
+    rng::SimRng,
+    run_config::RunConfig,
+    scenario::{
+        movement::resolve_moves,
+        species::{remap_species, resolve_predation, species_name, species_named},
+    },
+    selection::{bank_phase_score, calculate_score, select_breeders},
+    services::{
+        checkpoints::{
+            checkpoint_path, save_checkpoint, Checkpoint, CheckpointRef, CHECKPOINT_VERSION,
+        },
+        dot::write_dot_file,
+        genomes::{save_genomes, GenomeFile, NeuronCounts, SavedGenome, GENOME_FILE_VERSION},
+    },
+    stats::GenerationStats,
 };
 use crate::{entity_config::EntityConfig, selection::select_survivors};
-use crate::{grid_config::GridConfig, scenario::scenario::Scenario};
+use crate::{
+    grid_config::GridConfig,
+    scenario::{curriculum::Curriculum, scenario::Scenario},
+};
 use crate::{render_config::RenderConfig, vector_2d::Vector2D};
 
+/// A genome on its way to another island, with the name of its species.
+pub type Migrant = (Vec<Gene>, Option<String>);
+
 pub struct LifeSim {
     entities: Vec<Entity>,
     sim_current_step: usize,
     sim_generation_number: u32,
+    last_generation_stats: Option<GenerationStats>,
+    occupancy: OccupancyGrid,
 
     scenario: Scenario,
+    curriculum: Option<Curriculum>,
+    rng: SimRng,
 
     grid_config: GridConfig,
     render_config: RenderConfig,
     entity_config: EntityConfig,
     network_config: NeuralNetworkConfig,
+    run_config: RunConfig,
 }
 
 impl LifeSim {
@@ -36,142 +70,501 @@ impl LifeSim {
         render_config: RenderConfig,
         entity_config: EntityConfig,
         network_config: NeuralNetworkConfig,
+        mut run_config: RunConfig,
+        mut rng: SimRng,
     ) -> Self {
-        let (entities, _) = spawn_entities(
-            &grid_config,
-            &network_config,
-            entity_config.start_count,
-            None,
-        );
+        let founders = mem::take(&mut run_config.founders);
+        let curriculum = run_config.curriculum.take();
+
+        let mut entities = if founders.is_empty() {
+            spawn_population(
+                &grid_config,
+                &scenario,
+                &network_config,
+                entity_config.start_count,
+                &mut rng,
+            )
+        } else {
+            spawn_founders(
+                &grid_config,
+                &scenario,
+                &founders,
+                entity_config.start_count,
+                &mut rng,
+            )
+        };
 
-        for i in 0..4 {
-            let Entity { brain, .. } = &entities[i];
-            write_dot_file(&brain, i);
+        give_starting_energy(&mut entities, &scenario);
+
+        for (i, Entity { brain, .. }) in entities.iter().enumerate().take(4) {
+            write_dot_file(&run_config.output_dir, brain, i);
         }
 
         Self {
+            occupancy: OccupancyGrid::new(grid_config.width, grid_config.height),
             scenario,
+            curriculum,
+            rng,
 
             entity_config,
             grid_config,
             render_config,
             network_config,
+            run_config,
 
             entities,
             sim_current_step: 0,
             sim_generation_number: 0,
+            last_generation_stats: None,
+        }
+    }
+
+    pub fn from_checkpoint(
+        checkpoint: Checkpoint,
+        grid_config: GridConfig,
+        render_config: RenderConfig,
+        entity_config: EntityConfig,
+        network_config: NeuralNetworkConfig,
+        run_config: RunConfig,
+    ) -> Self {
+        Self {
+            occupancy: OccupancyGrid::new(grid_config.width, grid_config.height),
+            scenario: checkpoint.scenario,
+            curriculum: checkpoint.curriculum,
+            rng: checkpoint.rng,
+
+            entity_config,
+            grid_config,
+            render_config,
+            network_config,
+            run_config,
+
+            entities: checkpoint.entities,
+            sim_current_step: checkpoint.current_step,
+            sim_generation_number: checkpoint.generation_number,
+            last_generation_stats: None,
+        }
+    }
+
+    pub fn checkpoint(&self) -> CheckpointRef<'_> {
+        CheckpointRef {
+            version: CHECKPOINT_VERSION,
+            generation_number: self.sim_generation_number,
+            current_step: self.sim_current_step,
+            entities: &self.entities,
+            scenario: &self.scenario,
+            curriculum: self.curriculum.as_ref(),
+            rng: &self.rng,
+        }
+    }
+
+    /// Writes the highest-scoring breeders of the generation to `<output_dir>/champions/latest`.
+    fn save_champions(&self, breeders: &[Entity], champion_count: usize) {
+        let mut champions: Vec<&Entity> = breeders.iter().collect();
+        champions.sort_by(|a, b| {
+            calculate_score(b, self.scenario.fitness_for(b.species))
+                .total_cmp(&calculate_score(a, self.scenario.fitness_for(a.species)))
+        });
+
+        let genome_file = GenomeFile {
+            version: GENOME_FILE_VERSION,
+            neurons: NeuronCounts::current(),
+            scenario: self.scenario.name.clone(),
+            genomes: champions
+                .into_iter()
+                .take(champion_count)
+                .map(|entity| SavedGenome {
+                    generation: self.sim_generation_number,
+                    score: calculate_score(entity, self.scenario.fitness_for(entity.species)),
+                    genome: entity.brain.genome.clone(),
+                })
+                .collect(),
+        };
+
+        let path = self.run_config.output_dir.join(format!(
+            "champions/latest.{}",
+            self.run_config.champion_format.extension()
+        ));
+
+        if let Err(err) = save_genomes(&path, &genome_file, self.run_config.champion_format) {
+            eprintln!("Warning: {}", err);
+        }
+    }
+
+    pub fn last_generation_stats(&self) -> Option<&GenerationStats> {
+        self.last_generation_stats.as_ref()
+    }
+
+    pub fn population(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// The genomes of the entities at `slots`, each with the name of its species.
+    pub fn genomes_at(&self, slots: &[usize]) -> Vec<Migrant> {
+        slots
+            .iter()
+            .map(|slot| {
+                let entity = &self.entities[*slot];
+                let species = species_name(&self.scenario, entity).map(str::to_owned);
+
+                (entity.brain.genome.clone(), species)
+            })
+            .collect()
+    }
+
+    /// Gives the entities at `slots` brains grown from `migrants`' genomes, in the same order, and
+    /// moves them into the species of the same name. Migrants from a species this island doesn't
+    /// have join its first species.
+    pub fn implant_genomes(&mut self, slots: &[usize], migrants: Vec<Migrant>) {
+        for (slot, (genome, species)) in slots.iter().zip(migrants) {
+            let entity = &mut self.entities[*slot];
+            entity.brain = Brain::from_genome(genome);
+            entity.species = species_named(&self.scenario, species.as_deref());
         }
     }
 
     fn start_new_generation(&mut self) {
-        let num_starting_entities = self.entities.len() as u32;
+        let mut stats =
+            GenerationStats::collect(self.sim_generation_number, &self.scenario, &self.entities);
+
         let entities = mem::take(&mut self.entities);
         let survivors = select_survivors(&self.scenario, entities);
-        let breeders = select_breeders(&self.scenario, &self.entity_config, survivors);
-
-        println!(
-            "Generation {} over. Breeders {}/{} ({:.2}%)",
-            self.sim_generation_number,
-            breeders.len(),
-            num_starting_entities,
-            breeders.len() as f32 / num_starting_entities as f32 * 100.0
+        stats.survivors = survivors.len() as u32;
+
+        let mut breeders = select_breeders(
+            &self.scenario,
+            &self.entity_config,
+            survivors,
+            &mut self.rng,
         );
+        stats.breeders = breeders.len() as u32;
+
+        if let Some(champion_count) = self.run_config.champion_count {
+            self.save_champions(&breeders, champion_count);
+        }
+
+        let next_stage = self
+            .curriculum
+            .as_mut()
+            .and_then(|curriculum| curriculum.next_generation(&stats));
 
-        let next_generation = spawn_next_generation(
+        if let Some(scenario) = next_stage {
+            stats.next_scenario = Some(scenario.name.clone());
+            remap_species(&mut breeders, &self.scenario, &scenario);
+            self.scenario = scenario;
+        } else {
+            // Selection is done under the last phase's rules, so the scenario can only go back to
+            // the first phase once breeders are picked.
+            self.scenario.reset(&mut self.rng);
+        }
+
+        let (mut next_generation, mutations) = spawn_next_generation(
             &self.grid_config,
             &self.entity_config,
             &self.network_config,
-            self.scenario.supplement_population,
-            self.scenario.limit_population,
+            &self.scenario,
             breeders,
+            &mut self.rng,
         );
 
+        stats.mutations = mutations;
+
+        give_starting_energy(&mut next_generation, &self.scenario);
+
+        stats.island = self.run_config.island;
+
+        if let Some(stats_writer) = &mut self.run_config.stats_writer {
+            if let Err(err) = stats_writer.write(&stats) {
+                eprintln!("Warning: could not write stats: {}", err);
+            }
+        }
+
+        self.last_generation_stats = Some(stats);
+
         for (i, entity) in next_generation.iter().enumerate().take(4) {
-            write_dot_file(&entity.brain, i);
+            write_dot_file(&self.run_config.output_dir, &entity.brain, i);
         }
 
         self.entities = next_generation;
         self.sim_generation_number += 1;
         self.sim_current_step = 0;
+
+        if let Some(interval) = self.run_config.checkpoint_interval {
+            if self.sim_generation_number.is_multiple_of(interval) {
+                let path = checkpoint_path(&self.run_config.output_dir, self.sim_generation_number);
+
+                // A failed checkpoint shouldn't end a long run, so just report it.
+                if let Err(err) = save_checkpoint(&path, &self.checkpoint()) {
+                    eprintln!("Warning: {}", err);
+                }
+            }
+        }
     }
 }
 
-type EntityColors = HashMap<(u32, u32), f64>;
+impl Progress for LifeSim {
+    fn is_finished(&self) -> bool {
+        self.run_config
+            .generation_limit
+            .is_some_and(|limit| self.sim_generation_number >= limit)
+    }
+
+    fn generation_number(&self) -> u32 {
+        self.sim_generation_number
+    }
+
+    fn latest_stats(&self) -> Vec<&GenerationStats> {
+        self.last_generation_stats.iter().collect()
+    }
+}
+
+/// How many cells ahead entities can see walls.
+const OBSTACLE_SIGHT_RANGE: u32 = 10;
+
+/// The colour entities are drawn in unless their species has its own.
+const ENTITY_COLOR: [u8; 4] = [0, 140, 200, 255];
+
+pub type EntityColors = HashMap<(u32, u32), [u8; 4]>;
 
 impl Automata<EntityColors> for LifeSim {
     fn update(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
         let generation_time =
             self.sim_current_step as f32 / self.scenario.generation_step_count as f32;
 
-        self.scenario.update(self.sim_current_step);
-
-        for entity in &mut self.entities {
-            if !entity.body.is_alive {
-                continue;
+        if let Some(ended) = self.scenario.update(self.sim_current_step, &mut self.rng) {
+            // Species with a fitness of their own keep it through every phase.
+            for entity in self
+                .entities
+                .iter_mut()
+                .filter(|entity| !self.scenario.has_own_fitness(entity.species))
+            {
+                bank_phase_score(entity, &ended, &self.scenario.fitness);
             }
+        }
 
-            if let Some(radiation) = self.scenario.radiation.as_ref() {
-                if self
-                    .scenario
-                    .is_point_in_rad_zone((entity.body.x, entity.body.y), self.sim_current_step)
-                {
-                    entity.times_irradiated += 1;
-
-                    if let Some(death_threshold) = radiation.death_threshold {
-                        if entity.times_irradiated >= death_threshold {
-                            entity.body.is_alive = false;
-                            continue;
+        // Everyone senses where the others stood at the start of the step. Entities whose brains
+        // have no use for it are spared the search.
+        self.occupancy.fill(&self.entities);
+        let neighbour_senses: Vec<Option<NeighbourSenses>> = (0..self.entities.len())
+            .into_par_iter()
+            .map(|i| {
+                let entity = &self.entities[i];
+
+                entity.body.is_alive.then(|| {
+                    if entity.brain.senses_neighbours() {
+                        sense_neighbours(&self.occupancy, &self.entities, &self.scenario, i)
+                    } else {
+                        NeighbourSenses::NOTHING
+                    }
+                })
+            })
+            .collect();
+
+        let scenario = &self.scenario;
+        let current_step = self.sim_current_step;
+
+        // Sensing and deciding only read shared state, so every entity can do it in parallel.
+        let decisions: Vec<Option<OutputNeuron>> = self
+            .entities
+            .par_iter_mut()
+            .zip(&neighbour_senses)
+            .map(|(entity, neighbour_senses)| {
+                let neighbour_senses = neighbour_senses.as_ref()?;
+
+                entity.steps_alive += 1;
+
+                if let Some(radiation) = scenario.radiation.as_ref() {
+                    let intensity = scenario
+                        .rad_intensity_at_point((entity.body.x, entity.body.y), current_step);
+
+                    entity.radiation_dose *= 1.0 - radiation.recovery;
+
+                    if intensity > 0.0 {
+                        entity.times_irradiated += 1;
+                        entity.radiation_dose += intensity;
+                    }
+
+                    if let Some(penalty) = radiation.penalty {
+                        entity.radiation_penalty += penalty.at(entity.radiation_dose);
+                    }
+
+                    if let Some(death) = radiation.death {
+                        if death.is_fatal(entity.radiation_dose, &mut entity.rng) {
+                            entity.body.die(DeathCause::Radiation);
+                            return None;
                         }
                     }
+                };
+
+                if let Some(energy) = scenario.energy.as_ref() {
+                    entity.body.energy -= energy.passive_cost;
+
+                    if entity.body.energy <= 0.0 {
+                        entity.body.die(DeathCause::Starvation);
+                        return None;
+                    }
                 }
-            };
 
-            let (rad_zone_dist, rad_zone_disp) = self
-                .scenario
-                .shortest_rad_zone_displacement((entity.body.x, entity.body.y));
+                let (rad_zone_dist, rad_zone_disp) = scenario
+                    .shortest_rad_zone_displacement((entity.body.x, entity.body.y), current_step);
 
-            let rad_zone_dir = rad_zone_disp.normalize();
-            let danger_angle = rad_zone_dir.y.atan2(rad_zone_dir.x);
+                let rad_zone_dir = rad_zone_disp.normalize();
+                let danger_angle = rad_zone_dir.y.atan2(rad_zone_dir.x);
 
-            let mut food_angle: f32 = 0.0;
+                let mut food_angle: f32 = 0.0;
 
-            if self.scenario.food.is_some() {
-                let (_, food_disp) = self
-                    .scenario
-                    .shortest_food_displacement((entity.body.x, entity.body.y));
+                if scenario.food.is_some() {
+                    let (_, food_disp) =
+                        scenario.shortest_food_displacement((entity.body.x, entity.body.y));
+
+                    let food_dist_xy = Vector2D {
+                        x: food_disp.0 as f32,
+                        y: food_disp.1 as f32,
+                    };
+
+                    let food_dir = food_dist_xy.normalize();
+                    food_angle = food_dir.y.atan2(food_dir.x);
+                }
 
-                let food_dist_xy = Vector2D {
-                    x: food_disp.0 as f32,
-                    y: food_disp.1 as f32,
+                let pos = (entity.body.x, entity.body.y);
+                let (fx, fy) = entity.body.facing;
+                let side_blocked = |(dx, dy): (i32, i32)| {
+                    let side = (pos.0 as i64 + dx as i64, pos.1 as i64 + dy as i64);
+                    if scenario.is_blocked(side) {
+                        1.0
+                    } else {
+                        0.0
+                    }
                 };
 
-                let food_dir = food_dist_xy.normalize();
-                food_angle = food_dir.y.atan2(food_dir.x);
-            }
+                let clear_cells =
+                    scenario.clear_distance(pos, entity.body.facing, OBSTACLE_SIGHT_RANGE);
+
+                let senses = Senses {
+                    generation_time,
+                    danger_dist: rad_zone_dist,
+                    danger_dir_sin: danger_angle.sin(),
+                    danger_dir_cos: danger_angle.cos(),
+                    food_dir_sin: food_angle.sin(),
+                    food_dir_cos: food_angle.cos(),
+                    blocked: if entity.body.blocked { 1.0 } else { 0.0 },
+                    energy: scenario
+                        .energy
+                        .as_ref()
+                        .map_or(0.0, |energy| entity.body.energy / energy.max),
+                    obstacle_ahead: if clear_cells < OBSTACLE_SIGHT_RANGE {
+                        1.0 - clear_cells as f32 / OBSTACLE_SIGHT_RANGE as f32
+                    } else {
+                        0.0
+                    },
+                    // Left and right are relative to the way the entity last moved.
+                    blocked_left: side_blocked((fy, -fx)),
+                    blocked_right: side_blocked((-fy, fx)),
+                    other_species_dist: neighbour_senses.other_species_dist,
+                    other_species_dir_sin: neighbour_senses.other_species_dir_sin,
+                    other_species_dir_cos: neighbour_senses.other_species_dir_cos,
+                    neighbour_density: neighbour_senses.density,
+                    neighbour_dist: neighbour_senses.nearest_dist,
+                    neighbour_dir_sin: neighbour_senses.nearest_dir_sin,
+                    neighbour_dir_cos: neighbour_senses.nearest_dir_cos,
+                    kin_similarity: neighbour_senses.kin_similarity,
+                };
 
-            let pos = (entity.body.x as u32, entity.body.y as u32);
+                Some(entity.brain.decide(&senses, &mut entity.rng))
+            })
+            .collect();
+
+        // Anything that changes shared state is applied afterwards in entity order, so that when
+        // two entities compete for the same food or cell the result doesn't depend on thread timing.
+        for (entity, senses) in self.entities.iter().zip(&neighbour_senses) {
+            // Entities that were alive when the grid was filled and died since no longer take up
+            // their cell.
+            if senses.is_some() && !entity.body.is_alive {
+                self.occupancy.remove((entity.body.x, entity.body.y));
+            }
+        }
 
-            if self.scenario.food.is_some() {
-                if self.scenario.is_food_at_point(pos) {
-                    self.scenario.consume_food_at_point(pos);
+        let starts: Vec<(u32, u32)> = self
+            .entities
+            .iter()
+            .map(|entity| (entity.body.x, entity.body.y))
+            .collect();
+
+        let mut targets: Vec<Option<(u32, u32)>> = self
+            .entities
+            .iter_mut()
+            .zip(decisions)
+            .map(|(entity, decision)| {
+                let decision = decision?;
+                let pos = (entity.body.x, entity.body.y);
+
+                if self.scenario.food.is_some() && self.scenario.is_food_at_point(pos) {
+                    let nutrition = self.scenario.consume_food_at_point(pos);
                     entity.times_eaten += 1;
+
+                    if let Some(energy) = &self.scenario.energy {
+                        entity.body.energy =
+                            (entity.body.energy + energy.food_value * nutrition).min(energy.max);
+                    }
+                }
+
+                let target =
+                    entity
+                        .body
+                        .next_position(decision, &self.grid_config, &mut entity.rng);
+
+                if target != pos {
+                    entity.body.facing = (
+                        (target.0 as i64 - pos.0 as i64).signum() as i32,
+                        (target.1 as i64 - pos.1 as i64).signum() as i32,
+                    );
                 }
-            }
 
-            let decision = entity.brain.decide(
-                generation_time,
-                rad_zone_dist,
-                danger_angle.sin(),
-                danger_angle.cos(),
-                food_angle.sin(),
-                food_angle.cos(),
+                // Walls stop movement the same way the edges of the grid do.
+                if self.scenario.is_wall(target) {
+                    Some(pos)
+                } else {
+                    Some(target)
+                }
+            })
+            .collect();
+
+        if self.scenario.has_hunters() {
+            resolve_predation(
+                &self.scenario,
+                &mut self.entities,
+                &mut targets,
+                &mut self.occupancy,
             );
+        }
+
+        resolve_moves(
+            self.scenario.movement,
+            &mut self.entities,
+            &targets,
+            &mut self.occupancy,
+        );
 
-            entity.body.update(decision, &self.grid_config);
+        for entity in &mut self.entities {
+            if entity.body.is_alive {
+                self.scenario.survival.visit(entity);
+            }
+        }
+
+        // Running out of energy by moving is fatal on the next step, like any other shortfall.
+        if let Some(energy) = &self.scenario.energy {
+            for (entity, start) in self.entities.iter_mut().zip(starts) {
+                if (entity.body.x, entity.body.y) != start {
+                    entity.body.energy -= energy.move_cost;
+                }
+            }
         }
 
         if self.sim_current_step >= self.scenario.generation_step_count {
-            self.scenario.reset();
             self.start_new_generation();
         } else {
             self.sim_current_step += 1;
@@ -185,10 +578,14 @@ impl Automata<EntityColors> for LifeSim {
 
         for entity in &self.entities {
             if entity.body.is_alive {
-                entity_colors.insert(
-                    (entity.body.x as u32, entity.body.y as u32),
-                    entity.body.color_gradient_index,
-                );
+                let color = self
+                    .scenario
+                    .species
+                    .get(entity.species)
+                    .and_then(|species| species.color)
+                    .unwrap_or(ENTITY_COLOR);
+
+                entity_colors.insert((entity.body.x, entity.body.y), color);
             }
         }
 
@@ -204,19 +601,27 @@ impl Automata<EntityColors> for LifeSim {
 
         let (x, y) = viewport_to_grid(vx, vy, self.render_config.pixel_scale);
 
-        let color: [u8; 4] = if entity_colors.contains_key(&(x, y)) {
-            [0, 140, 200, 255]
+        let color: [u8; 4] = if let Some(color) = entity_colors.get(&(x, y)) {
+            *color
+        } else if self.scenario.is_wall((x, y)) {
+            self.render_config.wall_color
         } else if self.scenario.food.is_some() && self.scenario.is_food_at_point((x, y)) {
             [20, 200, 0, 255]
         } else {
             self.render_config.background_color
         };
 
-        let color = if self
+        let intensity = self
             .scenario
-            .is_point_in_rad_zone((x, y), self.sim_current_step)
-        {
-            additive_blend(self.render_config.rad_zone_color, color)
+            .rad_intensity_at_point((x, y), self.sim_current_step);
+
+        let color = if intensity > 0.0 {
+            let rad_zone_color = self
+                .render_config
+                .rad_zone_color
+                .map(|channel| (channel as f32 * intensity.min(1.0)) as u8);
+
+            additive_blend(rad_zone_color, color)
         } else {
             color
         };
@@ -1,41 +1,68 @@
+mod archipelago;
 mod body;
+mod cli;
 mod entity;
 mod entity_config;
 mod genome;
 mod grid_config;
+mod headless;
 mod life_sim;
+mod neighbours;
 pub mod neural_network;
 mod neural_network_config;
+mod occupancy;
 mod render_config;
 mod rendering;
+mod rng;
+mod run_config;
 mod scenario;
 mod selection;
 mod services;
 mod settings;
+mod stats;
 mod vector_2d;
 
-use cellular_automata::{sim::run_sim, sim_config::SimConfig};
+use std::{error::Error, process};
 
+use archipelago::Archipelago;
+use cellular_automata::{automata::Automata, sim::run_sim, sim_config::SimConfig};
+use clap::Parser;
+
+use cli::Cli;
 use entity_config::EntityConfig;
 use grid_config::GridConfig;
-use life_sim::LifeSim;
+use headless::{run_headless, Progress};
+use life_sim::{EntityColors, LifeSim};
 use neural_network_config::NeuralNetworkConfig;
-use pixels::Error;
 use render_config::RenderConfig;
-use scenario::scenario::Scenario;
-use services::scenarios::load_scenario;
+use rng::{derive_rng, seeded_rng, SimRng};
+use run_config::RunConfig;
+use scenario::{curriculum::Curriculum, scenario::Scenario};
+use services::{
+    checkpoints::load_checkpoint, curricula::load_curriculum, genomes::load_genomes,
+    islands::load_islands, scenarios::load_scenario, stats::StatsWriter,
+};
 use settings::Settings;
 
-fn main() -> Result<(), Error> {
-    let settings = Settings::new().unwrap();
+fn main() {
+    let cli = Cli::parse();
 
-    let render_config = RenderConfig {
-        pixel_scale: settings.render_pixel_scale,
-        rad_zone_color: settings.render_rad_zone_color,
-        background_color: settings.render_background_color,
-        viewport_width: settings.render_pixel_scale * settings.grid_width,
-        viewport_height: settings.render_pixel_scale * settings.grid_height,
-    };
+    if let Err(err) = run(cli) {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let settings = Settings::new(&cli.settings)?;
+
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build_global()?;
+    }
+
+    let render_config = render_config(&settings, settings.grid_width, settings.grid_height);
 
     let entity_config = EntityConfig {
         start_count: settings.entity_start_count,
@@ -55,21 +82,271 @@ fn main() -> Result<(), Error> {
         mutation_rate: settings.neural_network_mutation_rate,
     };
 
-    let scenario_file = load_scenario("wave").unwrap();
-    let scenario = Scenario::from_file(scenario_file, grid_config.width, grid_config.height);
-
-    let sim = Box::new(LifeSim::new(
-        scenario,
-        grid_config,
-        render_config,
-        entity_config,
-        network_config,
-    ));
-
-    run_sim(
-        sim,
-        Some(SimConfig {
-            debug: settings.debug,
-        }),
-    )
+    let mut run_config = RunConfig {
+        generation_limit: cli.generations,
+        output_dir: cli.output_dir,
+        checkpoint_interval: cli.checkpoint_interval,
+        founders: Vec::new(),
+        curriculum: None,
+        champion_count: cli.champions.map(|count| count as usize),
+        champion_format: cli.champion_format,
+        stats_writer: Some(
+            StatsWriter::new(
+                cli.stats_format,
+                cli.stats_file.as_deref(),
+                cli.resume.is_some(),
+            )
+            .map_err(|err| format!("could not open stats output: {}", err))?,
+        ),
+        island: None,
+    };
+
+    std::fs::create_dir_all(run_config.output_dir.join("dots")).map_err(|err| {
+        format!(
+            "could not create output directory {}: {}",
+            run_config.output_dir.display(),
+            err
+        )
+    })?;
+
+    let sim = if let Some(checkpoint_path) = &cli.resume {
+        let checkpoint = load_checkpoint(checkpoint_path)?;
+
+        if checkpoint.scenario.grid_size() != (grid_config.width, grid_config.height) {
+            return Err(format!(
+                "checkpoint {} was made with a {}x{} grid, but the settings specify {}x{}",
+                checkpoint_path.display(),
+                checkpoint.scenario.grid_size().0,
+                checkpoint.scenario.grid_size().1,
+                grid_config.width,
+                grid_config.height
+            )
+            .into());
+        }
+
+        eprintln!(
+            "Resuming from generation {}, step {}",
+            checkpoint.generation_number, checkpoint.current_step
+        );
+
+        if let Some(curriculum) = &checkpoint.curriculum {
+            eprintln!(
+                "Curriculum stage {} of {} ({})",
+                curriculum.stage() + 1,
+                curriculum.stage_count(),
+                checkpoint.scenario.name
+            );
+        }
+
+        LifeSim::from_checkpoint(
+            checkpoint,
+            grid_config,
+            render_config,
+            entity_config,
+            network_config,
+            run_config,
+        )
+    } else {
+        let seed = cli.seed.unwrap_or_else(rand::random);
+        let mut rng = seeded_rng(seed);
+
+        if let Some(founders) = &cli.founders {
+            let genome_file = load_genomes(founders)?;
+            eprintln!(
+                "Founding population from {} genomes evolved in {}",
+                genome_file.genomes.len(),
+                genome_file.scenario
+            );
+
+            run_config.founders = genome_file
+                .genomes
+                .into_iter()
+                .map(|saved| saved.genome)
+                .collect();
+        }
+
+        if let Some(islands) = &cli.islands {
+            let archipelago = build_archipelago(
+                islands,
+                &settings,
+                entity_config,
+                network_config,
+                run_config,
+                rng,
+            )?;
+            eprintln!("Seed: {}", seed);
+
+            return start(archipelago, cli.headless, settings.debug);
+        }
+
+        let (scenario, curriculum) = if let Some(curriculum) = &cli.curriculum {
+            let curriculum_file = load_curriculum(
+                curriculum,
+                grid_config.width,
+                grid_config.height,
+                entity_config.start_count,
+            )?;
+            eprintln!(
+                "Curriculum {} with {} stages",
+                curriculum_file.name,
+                curriculum_file.stages.len()
+            );
+
+            let stages = curriculum_file
+                .scenarios
+                .into_iter()
+                .zip(curriculum_file.stages)
+                .map(|(scenario_file, stage)| {
+                    let scenario = Scenario::from_file(
+                        scenario_file,
+                        grid_config.width,
+                        grid_config.height,
+                        &mut rng,
+                    );
+
+                    (scenario, stage.until)
+                })
+                .collect();
+
+            let (scenario, curriculum) = Curriculum::new(stages);
+            (scenario, Some(curriculum))
+        } else {
+            let scenario_file = load_scenario(
+                &cli.scenario,
+                grid_config.width,
+                grid_config.height,
+                entity_config.start_count,
+            )?;
+            let scenario = Scenario::from_file(
+                scenario_file,
+                grid_config.width,
+                grid_config.height,
+                &mut rng,
+            );
+
+            (scenario, None)
+        };
+
+        run_config.curriculum = curriculum;
+        eprintln!("Seed: {}", seed);
+
+        LifeSim::new(
+            scenario,
+            grid_config,
+            render_config,
+            entity_config,
+            network_config,
+            run_config,
+            rng,
+        )
+    };
+
+    start(sim, cli.headless, settings.debug)
+}
+
+fn start(
+    sim: impl Automata<EntityColors> + Progress + 'static,
+    headless: bool,
+    debug: bool,
+) -> Result<(), Box<dyn Error>> {
+    if headless {
+        run_headless(sim);
+        return Ok(());
+    }
+
+    run_sim(Box::new(sim), Some(SimConfig { debug }))?;
+
+    Ok(())
+}
+
+fn render_config(settings: &Settings, grid_width: u32, grid_height: u32) -> RenderConfig {
+    RenderConfig {
+        pixel_scale: settings.render_pixel_scale,
+        rad_zone_color: settings.render_rad_zone_color,
+        background_color: settings.render_background_color,
+        wall_color: settings.render_wall_color,
+        viewport_width: settings.render_pixel_scale * grid_width,
+        viewport_height: settings.render_pixel_scale * grid_height,
+    }
+}
+
+/// Sets up a `LifeSim` for every island in an islands file. Each island gets its own generator
+/// derived from `rng`, and writes its files to `<output_dir>/islands/<n>`.
+fn build_archipelago(
+    islands: &str,
+    settings: &Settings,
+    entity_config: EntityConfig,
+    network_config: NeuralNetworkConfig,
+    mut run_config: RunConfig,
+    mut rng: SimRng,
+) -> Result<Archipelago, Box<dyn Error>> {
+    let islands_file = load_islands(
+        islands,
+        (settings.grid_width, settings.grid_height),
+        entity_config.start_count,
+    )?;
+    eprintln!(
+        "Archipelago {} with {} islands",
+        islands_file.name,
+        islands_file.islands.len()
+    );
+
+    let mut sims = Vec::with_capacity(islands_file.islands.len());
+
+    for (i, (island, scenario_file)) in islands_file
+        .islands
+        .into_iter()
+        .zip(islands_file.scenarios)
+        .enumerate()
+    {
+        let (width, height) = island
+            .grid
+            .unwrap_or((settings.grid_width, settings.grid_height));
+        let mut island_rng = derive_rng(&mut rng);
+        let scenario = Scenario::from_file(scenario_file, width, height, &mut island_rng);
+
+        let output_dir = run_config.output_dir.join(format!("islands/{}", i));
+        std::fs::create_dir_all(output_dir.join("dots")).map_err(|err| {
+            format!(
+                "could not create output directory {}: {}",
+                output_dir.display(),
+                err
+            )
+        })?;
+
+        let island_run_config = RunConfig {
+            generation_limit: run_config.generation_limit,
+            output_dir,
+            checkpoint_interval: None,
+            founders: run_config.founders.clone(),
+            curriculum: None,
+            champion_count: run_config.champion_count,
+            champion_format: run_config.champion_format,
+            stats_writer: None,
+            island: Some(i as u32),
+        };
+
+        sims.push(LifeSim::new(
+            scenario,
+            GridConfig { width, height },
+            render_config(settings, width, height),
+            entity_config.clone(),
+            network_config.clone(),
+            island_run_config,
+            island_rng,
+        ));
+    }
+
+    let stats_writer = run_config
+        .stats_writer
+        .take()
+        .expect("The stats writer should be opened before islands are built.");
+
+    Ok(Archipelago::new(
+        sims,
+        islands_file.migration,
+        run_config.generation_limit,
+        stats_writer,
+        rng,
+    ))
 }
@@ -0,0 +1,112 @@
+use crate::{
+    entity::Entity, genome::genome_similarity, occupancy::OccupancyGrid,
+    scenario::scenario::Scenario,
+};
+
+/// How many cells around an entity count towards the density it senses.
+const DENSITY_RADIUS: u32 = 2;
+
+/// How far away entities can see each other.
+const ENTITY_SIGHT_RANGE: u32 = 10;
+
+/// What an entity perceives of the entities around it.
+pub struct NeighbourSenses {
+    pub density: f32,
+    pub nearest_dist: f32,
+    pub nearest_dir_sin: f32,
+    pub nearest_dir_cos: f32,
+    pub kin_similarity: f32,
+    pub other_species_dist: f32,
+    pub other_species_dir_sin: f32,
+    pub other_species_dir_cos: f32,
+}
+
+impl NeighbourSenses {
+    /// What an entity senses with nobody around.
+    pub const NOTHING: NeighbourSenses = NeighbourSenses {
+        density: 0.0,
+        nearest_dist: 1.0,
+        nearest_dir_sin: 0.0,
+        nearest_dir_cos: 0.0,
+        kin_similarity: 0.0,
+        other_species_dist: 1.0,
+        other_species_dir_sin: 0.0,
+        other_species_dir_cos: 0.0,
+    };
+}
+
+/// Distance and direction from one cell to another. The distance is a fraction of the sight range,
+/// and is 1 with no direction when there's nothing in sight.
+fn bearing(from: (u32, u32), to: Option<(u32, u32)>) -> (f32, f32, f32) {
+    match to {
+        Some(to) => {
+            let dx = to.0 as f32 - from.0 as f32;
+            let dy = to.1 as f32 - from.1 as f32;
+            let angle = dy.atan2(dx);
+            let dist = (dx.hypot(dy) / ENTITY_SIGHT_RANGE as f32).min(1.0);
+
+            (dist, angle.sin(), angle.cos())
+        }
+        None => (1.0, 0.0, 0.0),
+    }
+}
+
+/// Senses the entities around the entity at index `entity`, as they stood when `occupancy` was
+/// last filled.
+pub fn sense_neighbours(
+    occupancy: &OccupancyGrid,
+    entities: &[Entity],
+    scenario: &Scenario,
+    entity: usize,
+) -> NeighbourSenses {
+    let position = (entities[entity].body.x, entities[entity].body.y);
+    let species = scenario.species_of(&entities[entity]);
+
+    // The entity itself is counted along with the others.
+    let others = occupancy
+        .count_around(position, DENSITY_RADIUS)
+        .saturating_sub(1);
+    let cells = (DENSITY_RADIUS * 2 + 1).pow(2) - 1;
+
+    let nearest = occupancy.nearest(position, ENTITY_SIGHT_RANGE, |other| other != entity);
+    let (nearest_dist, nearest_dir_sin, nearest_dir_cos) =
+        bearing(position, nearest.map(|(_, position)| position));
+
+    // The nearest entity is also the nearest of whichever kind it is, so only the other kind needs
+    // a search of its own.
+    let nearest_is_kin = nearest.map(|(other, _)| scenario.species_of(&entities[other]) == species);
+
+    // Comparing genomes is the costly part, so only brains that read it look for kin.
+    let kin = match nearest_is_kin {
+        _ if !entities[entity].brain.senses_kin() => None,
+        Some(false) => occupancy.nearest(position, ENTITY_SIGHT_RANGE, |other| {
+            other != entity && scenario.species_of(&entities[other]) == species
+        }),
+        _ => nearest,
+    };
+
+    let other_species = match nearest_is_kin {
+        Some(true) if scenario.species.len() > 1 => {
+            occupancy.nearest(position, ENTITY_SIGHT_RANGE, |other| {
+                scenario.species_of(&entities[other]) != species
+            })
+        }
+        Some(false) => nearest,
+        _ => None,
+    };
+    let (other_species_dist, other_species_dir_sin, other_species_dir_cos) =
+        bearing(position, other_species.map(|(_, position)| position));
+
+    NeighbourSenses {
+        density: (others as f32 / cells as f32).min(1.0),
+        nearest_dist,
+        nearest_dir_sin,
+        nearest_dir_cos,
+        kin_similarity: kin.map_or(0.0, |(kin, _)| {
+            genome_similarity(&entities[entity].brain.genome, &entities[kin].brain.genome)
+        }),
+        other_species_dist,
+        other_species_dir_sin,
+        other_species_dir_cos,
+    }
+}
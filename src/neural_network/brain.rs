@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::genome::gene::Gene;
 
 use super::connection::Connection;
@@ -7,6 +10,7 @@ use super::hidden_neuron::HiddenNeuron;
 use super::input_neuron::InputNeuron;
 use super::neuron_kind::NeuronKind;
 use super::output_neuron::OutputNeuron;
+use super::senses::Senses;
 
 #[derive(Debug)]
 pub struct Brain {
@@ -116,15 +120,26 @@ impl Brain {
         }
     }
 
-    pub fn decide(
-        &mut self,
-        generation_time: f32,
-        danger_dist: f32,
-        danger_dir_sin: f32,
-        danger_dir_cos: f32,
-        food_dir_sin: f32,
-        food_dir_cos: f32,
-    ) -> OutputNeuron {
+    /// Whether any of the brain's inputs read what the entity senses of the entities around it.
+    pub fn senses_neighbours(&self) -> bool {
+        self.has_input(|input| input.senses_neighbours())
+    }
+
+    /// Whether the brain reads how alike the entity and its nearest kin are.
+    pub fn senses_kin(&self) -> bool {
+        self.has_input(|input| input == InputNeuron::KinSimilarity)
+    }
+
+    fn has_input(&self, wanted: impl Fn(InputNeuron) -> bool) -> bool {
+        self.input_neurons
+            .iter()
+            .any(|index| match &self.neurons[*index as usize] {
+                NeuronKind::Input(input) => wanted(*input),
+                _ => false,
+            })
+    }
+
+    pub fn decide(&mut self, senses: &Senses, rng: &mut impl Rng) -> OutputNeuron {
         let mut signals = vec![0.0; self.neurons.len()];
 
         // Initialize input signals.
@@ -133,7 +148,7 @@ impl Brain {
 
             let raw_signal: f32 = match input {
                 NeuronKind::Input(input) => match input {
-                    InputNeuron::Random => rand::random::<f32>(),
+                    InputNeuron::Random => rng.gen::<f32>(),
                     InputNeuron::PreviousMoveDirCos => match self.previous_move {
                         OutputNeuron::MoveLeft => -1.0,
                         OutputNeuron::MoveRight => 1.0,
@@ -150,12 +165,25 @@ impl Brain {
                         OutputNeuron::Stay => 0.0,
                         OutputNeuron::MoveRandom => 0.0,
                     },
-                    InputNeuron::Time => generation_time,
-                    InputNeuron::DangerDist => danger_dist,
-                    InputNeuron::DangerDirCos => danger_dir_cos,
-                    InputNeuron::DangerDirSin => danger_dir_sin,
-                    InputNeuron::FoodDirCos => food_dir_cos,
-                    InputNeuron::FoodDirSin => food_dir_sin,
+                    InputNeuron::Time => senses.generation_time,
+                    InputNeuron::DangerDist => senses.danger_dist,
+                    InputNeuron::DangerDirCos => senses.danger_dir_cos,
+                    InputNeuron::DangerDirSin => senses.danger_dir_sin,
+                    InputNeuron::FoodDirCos => senses.food_dir_cos,
+                    InputNeuron::FoodDirSin => senses.food_dir_sin,
+                    InputNeuron::Blocked => senses.blocked,
+                    InputNeuron::Energy => senses.energy,
+                    InputNeuron::ObstacleAhead => senses.obstacle_ahead,
+                    InputNeuron::BlockedLeft => senses.blocked_left,
+                    InputNeuron::BlockedRight => senses.blocked_right,
+                    InputNeuron::OtherSpeciesDist => senses.other_species_dist,
+                    InputNeuron::OtherSpeciesDirCos => senses.other_species_dir_cos,
+                    InputNeuron::OtherSpeciesDirSin => senses.other_species_dir_sin,
+                    InputNeuron::NeighbourDensity => senses.neighbour_density,
+                    InputNeuron::NeighbourDist => senses.neighbour_dist,
+                    InputNeuron::NeighbourDirCos => senses.neighbour_dir_cos,
+                    InputNeuron::NeighbourDirSin => senses.neighbour_dir_sin,
+                    InputNeuron::KinSimilarity => senses.kin_similarity,
                 },
                 _ => panic!("Input layer should only contain input neurons."),
             };
@@ -212,6 +240,43 @@ impl Brain {
     }
 }
 
+// Everything but the previous move is derived from the genome, so that's all that gets saved.
+#[derive(Serialize)]
+struct BrainStateRef<'a> {
+    genome: &'a [Gene],
+    previous_move: OutputNeuron,
+}
+
+#[derive(Deserialize)]
+struct BrainState {
+    genome: Vec<Gene>,
+    previous_move: OutputNeuron,
+}
+
+impl Serialize for Brain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BrainStateRef {
+            genome: &self.genome,
+            previous_move: self.previous_move,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Brain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let BrainState {
+            genome,
+            previous_move,
+        } = BrainState::deserialize(deserializer)?;
+
+        let mut brain = Brain::from_genome(genome);
+        brain.previous_move = previous_move;
+
+        Ok(brain)
+    }
+}
+
 impl Clone for Brain {
     fn clone(&self) -> Self {
         Self {
@@ -13,9 +13,37 @@ pub enum InputNeuron {
     DangerDirCos,
     FoodDirSin,
     FoodDirCos,
+    Blocked,
+    Energy,
+    ObstacleAhead,
+    BlockedLeft,
+    BlockedRight,
+    OtherSpeciesDist,
+    OtherSpeciesDirSin,
+    OtherSpeciesDirCos,
+    NeighbourDensity,
+    NeighbourDist,
+    NeighbourDirSin,
+    NeighbourDirCos,
+    KinSimilarity,
 }
 
 impl InputNeuron {
+    /// Whether the neuron reads what the entity senses of the entities around it.
+    pub fn senses_neighbours(&self) -> bool {
+        matches!(
+            self,
+            InputNeuron::OtherSpeciesDist
+                | InputNeuron::OtherSpeciesDirSin
+                | InputNeuron::OtherSpeciesDirCos
+                | InputNeuron::NeighbourDensity
+                | InputNeuron::NeighbourDist
+                | InputNeuron::NeighbourDirSin
+                | InputNeuron::NeighbourDirCos
+                | InputNeuron::KinSimilarity
+        )
+    }
+
     pub fn from_discriminant(discriminant: usize) -> Self {
         let discriminant = discriminant % InputNeuron::iter().count();
 
@@ -29,6 +57,19 @@ impl InputNeuron {
             6 => InputNeuron::DangerDirCos,
             7 => InputNeuron::FoodDirSin,
             8 => InputNeuron::FoodDirCos,
+            9 => InputNeuron::Blocked,
+            10 => InputNeuron::Energy,
+            11 => InputNeuron::ObstacleAhead,
+            12 => InputNeuron::BlockedLeft,
+            13 => InputNeuron::BlockedRight,
+            14 => InputNeuron::OtherSpeciesDist,
+            15 => InputNeuron::OtherSpeciesDirSin,
+            16 => InputNeuron::OtherSpeciesDirCos,
+            17 => InputNeuron::NeighbourDensity,
+            18 => InputNeuron::NeighbourDist,
+            19 => InputNeuron::NeighbourDirSin,
+            20 => InputNeuron::NeighbourDirCos,
+            21 => InputNeuron::KinSimilarity,
             _ => panic!("Invalid discriminant for InputNeuron: {}", discriminant),
         }
     }
@@ -44,6 +85,19 @@ impl InputNeuron {
             InputNeuron::DangerDirCos => 6,
             InputNeuron::FoodDirSin => 7,
             InputNeuron::FoodDirCos => 8,
+            InputNeuron::Blocked => 9,
+            InputNeuron::Energy => 10,
+            InputNeuron::ObstacleAhead => 11,
+            InputNeuron::BlockedLeft => 12,
+            InputNeuron::BlockedRight => 13,
+            InputNeuron::OtherSpeciesDist => 14,
+            InputNeuron::OtherSpeciesDirSin => 15,
+            InputNeuron::OtherSpeciesDirCos => 16,
+            InputNeuron::NeighbourDensity => 17,
+            InputNeuron::NeighbourDist => 18,
+            InputNeuron::NeighbourDirSin => 19,
+            InputNeuron::NeighbourDirCos => 20,
+            InputNeuron::KinSimilarity => 21,
         }
     }
 }
@@ -60,6 +114,19 @@ impl fmt::Display for InputNeuron {
             InputNeuron::DangerDirCos => write!(f, "DangerDirCos"),
             InputNeuron::FoodDirSin => write!(f, "FoodDirSin"),
             InputNeuron::FoodDirCos => write!(f, "FoodDirCos"),
+            InputNeuron::Blocked => write!(f, "Blocked"),
+            InputNeuron::Energy => write!(f, "Energy"),
+            InputNeuron::ObstacleAhead => write!(f, "ObstacleAhead"),
+            InputNeuron::BlockedLeft => write!(f, "BlockedLeft"),
+            InputNeuron::BlockedRight => write!(f, "BlockedRight"),
+            InputNeuron::OtherSpeciesDist => write!(f, "OtherSpeciesDist"),
+            InputNeuron::OtherSpeciesDirSin => write!(f, "OtherSpeciesDirSin"),
+            InputNeuron::OtherSpeciesDirCos => write!(f, "OtherSpeciesDirCos"),
+            InputNeuron::NeighbourDensity => write!(f, "NeighbourDensity"),
+            InputNeuron::NeighbourDist => write!(f, "NeighbourDist"),
+            InputNeuron::NeighbourDirSin => write!(f, "NeighbourDirSin"),
+            InputNeuron::NeighbourDirCos => write!(f, "NeighbourDirCos"),
+            InputNeuron::KinSimilarity => write!(f, "KinSimilarity"),
         }
     }
 }
@@ -4,3 +4,4 @@ pub mod hidden_neuron;
 pub mod input_neuron;
 pub mod neuron_kind;
 pub mod output_neuron;
+pub mod senses;
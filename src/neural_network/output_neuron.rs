@@ -1,8 +1,10 @@
 use std::fmt;
+
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
-#[derive(Clone, Copy, Debug, EnumIter, EnumCount, PartialEq)]
+#[derive(Clone, Copy, Debug, EnumIter, EnumCount, PartialEq, Serialize, Deserialize)]
 pub enum OutputNeuron {
     MoveRandom,
     MoveUp,
@@ -0,0 +1,36 @@
+/// Everything an entity perceives about its surroundings on a given step. Each field feeds one or
+/// more `InputNeuron`s.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Senses {
+    pub generation_time: f32,
+    pub danger_dist: f32,
+    pub danger_dir_sin: f32,
+    pub danger_dir_cos: f32,
+    pub food_dir_sin: f32,
+    pub food_dir_cos: f32,
+    /// 1 if the entity's last move was blocked, 0 otherwise.
+    pub blocked: f32,
+    /// Stored energy as a fraction of the scenario's maximum, or 0 when energy isn't used.
+    pub energy: f32,
+    /// How close the nearest wall or grid edge straight ahead is, from 1 when it's right in front
+    /// to 0 when there's none in sight.
+    pub obstacle_ahead: f32,
+    /// 1 if the cell to the entity's left can't be entered, 0 otherwise.
+    pub blocked_left: f32,
+    /// 1 if the cell to the entity's right can't be entered, 0 otherwise.
+    pub blocked_right: f32,
+    /// Distance to the nearest living entity of another species, when there's one in sight.
+    pub other_species_dist: f32,
+    pub other_species_dir_sin: f32,
+    pub other_species_dir_cos: f32,
+    /// How crowded the cells around the entity are, from 0 when they're empty to 1 when there's
+    /// at least one other entity for every cell.
+    pub neighbour_density: f32,
+    /// Distance to the nearest other entity, when there's one in sight.
+    pub neighbour_dist: f32,
+    pub neighbour_dir_sin: f32,
+    pub neighbour_dir_cos: f32,
+    /// The fraction of genes the entity shares with the nearest entity of its own species, or 0
+    /// when there's none in sight.
+    pub kin_similarity: f32,
+}
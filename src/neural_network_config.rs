@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct NeuralNetworkConfig {
     pub hidden_layer_width: usize,
     pub hidden_layer_depth: usize,
@@ -0,0 +1,156 @@
+use cellular_automata::grid::grid_coords_to_index;
+
+use crate::entity::Entity;
+
+/// Where the living entities are on the grid. Keeps a count of the entities on each cell, which
+/// is kept up to date as they move and die, and a list of which entities were on each cell when
+/// the grid was last filled, for looking up neighbours.
+pub struct OccupancyGrid {
+    width: u32,
+    height: u32,
+    counts: Vec<u16>,
+    /// Where each cell's entities start in `residents`. Has one more entry than there are cells.
+    cell_starts: Vec<u32>,
+    /// Entity indices grouped by cell, in entity order within each cell.
+    residents: Vec<u32>,
+}
+
+impl OccupancyGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            counts: vec![0; (width * height) as usize],
+            cell_starts: vec![0; (width * height) as usize + 1],
+            residents: Vec::new(),
+        }
+    }
+
+    /// Recounts the grid from scratch and records which living entity is on which cell.
+    pub fn fill(&mut self, entities: &[Entity]) {
+        let width = self.width;
+        let alive = || {
+            entities
+                .iter()
+                .enumerate()
+                .filter(|(_, entity)| entity.body.is_alive)
+                .map(move |(i, entity)| {
+                    let cell = grid_coords_to_index(entity.body.x, entity.body.y, width);
+                    (i as u32, cell)
+                })
+        };
+
+        self.counts.fill(0);
+        self.cell_starts.fill(0);
+
+        for (_, cell) in alive() {
+            self.counts[cell] += 1;
+            self.cell_starts[cell + 1] += 1;
+        }
+
+        for cell in 1..self.cell_starts.len() {
+            self.cell_starts[cell] += self.cell_starts[cell - 1];
+        }
+
+        let mut next = self.cell_starts.clone();
+        self.residents.clear();
+        self.residents
+            .resize(*self.cell_starts.last().unwrap() as usize, 0);
+
+        for (i, cell) in alive() {
+            self.residents[next[cell] as usize] = i;
+            next[cell] += 1;
+        }
+    }
+
+    pub fn add(&mut self, (x, y): (u32, u32)) {
+        self.counts[grid_coords_to_index(x, y, self.width)] += 1;
+    }
+
+    pub fn remove(&mut self, (x, y): (u32, u32)) {
+        self.counts[grid_coords_to_index(x, y, self.width)] -= 1;
+    }
+
+    pub fn is_occupied(&self, (x, y): (u32, u32)) -> bool {
+        self.counts[grid_coords_to_index(x, y, self.width)] > 0
+    }
+
+    /// The entities that were on a cell when the grid was last filled. Some may have moved or died
+    /// since.
+    pub fn residents_at(&self, (x, y): (u32, u32)) -> impl Iterator<Item = usize> + '_ {
+        let cell = grid_coords_to_index(x, y, self.width);
+        let residents = self.cell_starts[cell] as usize..self.cell_starts[cell + 1] as usize;
+
+        self.residents[residents].iter().map(|i| *i as usize)
+    }
+
+    /// How many entities were within `radius` cells of `(x, y)` on either axis, counting any on
+    /// `(x, y)` itself.
+    pub fn count_around(&self, (x, y): (u32, u32), radius: u32) -> usize {
+        let mut count = 0;
+
+        for ny in y.saturating_sub(radius)..=(y + radius).min(self.height - 1) {
+            let row = grid_coords_to_index(0, ny, self.width);
+            let first = row + x.saturating_sub(radius) as usize;
+            let last = row + (x + radius).min(self.width - 1) as usize;
+
+            count += (self.cell_starts[last + 1] - self.cell_starts[first]) as usize;
+        }
+
+        count
+    }
+
+    /// Finds the closest entity to `(x, y)` within `range` cells that `accept` allows, with its
+    /// position. Ties are broken by row, then column, then entity order.
+    pub fn nearest(
+        &self,
+        (x, y): (u32, u32),
+        range: u32,
+        accept: impl Fn(usize) -> bool,
+    ) -> Option<(usize, (u32, u32))> {
+        let mut best: Option<(u64, (u32, u32), usize)> = None;
+
+        for ring in 0..=range {
+            // Everything in this ring or further out is at least `ring` cells away.
+            if best.is_some_and(|(dist, _, _)| dist < (ring as u64).pow(2)) {
+                break;
+            }
+
+            for (nx, ny) in self.ring_cells((x, y), ring) {
+                let Some(found) = self.residents_at((nx, ny)).find(|i| accept(*i)) else {
+                    continue;
+                };
+
+                let dist = (nx.abs_diff(x) as u64).pow(2) + (ny.abs_diff(y) as u64).pow(2);
+                let closer = best
+                    .is_none_or(|(best_dist, (bx, by), _)| (dist, ny, nx) < (best_dist, by, bx));
+
+                if closer {
+                    best = Some((dist, (nx, ny), found));
+                }
+            }
+        }
+
+        best.map(|(_, position, found)| (found, position))
+    }
+
+    /// The cells exactly `ring` cells away from `(x, y)` on either axis, clipped to the grid.
+    fn ring_cells(&self, (x, y): (u32, u32), ring: u32) -> impl Iterator<Item = (u32, u32)> {
+        let (x, y, ring) = (x as i64, y as i64, ring as i64);
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        (y - ring..=y + ring)
+            .flat_map(move |ny| {
+                let edge_row = ny == y - ring || ny == y + ring;
+                let step = if edge_row || ring == 0 {
+                    1
+                } else {
+                    (ring * 2) as usize
+                };
+
+                (x - ring..=x + ring).step_by(step).map(move |nx| (nx, ny))
+            })
+            .filter(move |&(nx, ny)| nx >= 0 && nx < width && ny >= 0 && ny < height)
+            .map(|(nx, ny)| (nx as u32, ny as u32))
+    }
+}
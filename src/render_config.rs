@@ -4,4 +4,5 @@ pub struct RenderConfig {
     pub viewport_height: u32,
     pub rad_zone_color: [u8; 4],
     pub background_color: [u8; 4],
+    pub wall_color: [u8; 4],
 }
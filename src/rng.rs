@@ -0,0 +1,16 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The generator used for all simulation randomness. Everything random is drawn from a `SimRng`
+/// owned by `LifeSim` (or derived from it), so a seed reproduces a run exactly.
+pub type SimRng = ChaCha8Rng;
+
+pub fn seeded_rng(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
+}
+
+/// Derives an independent generator from `parent`. Entities each own one of these, so the values
+/// they draw don't depend on the order in which other entities are updated.
+pub fn derive_rng(parent: &mut impl Rng) -> SimRng {
+    SimRng::seed_from_u64(parent.gen())
+}
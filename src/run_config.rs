@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use crate::{
+    genome::gene::Gene,
+    scenario::curriculum::Curriculum,
+    services::{genomes::GenomeFormat, stats::StatsWriter},
+};
+
+pub struct RunConfig {
+    pub generation_limit: Option<u32>,
+    pub output_dir: PathBuf,
+    pub checkpoint_interval: Option<u32>,
+
+    /// Genomes to found the first generation with. Random genomes are used when empty.
+    pub founders: Vec<Vec<Gene>>,
+    /// Stages to train through after the starting scenario. Only the starting scenario is run
+    /// when absent.
+    pub curriculum: Option<Curriculum>,
+    pub champion_count: Option<usize>,
+    pub champion_format: GenomeFormat,
+
+    /// Where each generation's stats are written. Islands leave this to their archipelago.
+    pub stats_writer: Option<StatsWriter>,
+    /// Which island this population is, when there are several.
+    pub island: Option<u32>,
+}
@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::GenerationStats;
+
+use super::scenario::Scenario;
+
+/// When a curriculum moves on from a stage. The stage ends as soon as any criterion given is met.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Advancement {
+    /// After this many generations in the stage.
+    pub generations: Option<u32>,
+    /// After a generation in which at least this fraction of the population survived.
+    pub survival_rate: Option<f32>,
+}
+
+impl Advancement {
+    fn is_met(&self, stage_generations: u32, stats: &GenerationStats) -> bool {
+        let survival_rate = stats.survivors as f32 / stats.population.max(1) as f32;
+
+        self.generations
+            .is_some_and(|generations| stage_generations >= generations)
+            || self
+                .survival_rate
+                .is_some_and(|threshold| survival_rate >= threshold)
+    }
+}
+
+/// A schedule of scenarios that a population is trained through, one stage after another.
+#[derive(Serialize, Deserialize)]
+pub struct Curriculum {
+    /// The scenarios of the stages still to come, in order.
+    upcoming: VecDeque<Scenario>,
+    /// When to move on from each stage. The last stage never ends.
+    advancement: Vec<Option<Advancement>>,
+    stage: usize,
+    /// Generations run in the current stage.
+    stage_generations: u32,
+}
+
+impl Curriculum {
+    /// Builds a curriculum from its stages, in order, and returns the first stage's scenario
+    /// alongside it.
+    pub fn new(stages: Vec<(Scenario, Option<Advancement>)>) -> (Scenario, Self) {
+        let (mut scenarios, advancement): (VecDeque<_>, Vec<_>) = stages.into_iter().unzip();
+        let first = scenarios
+            .pop_front()
+            .expect("A curriculum should have at least one stage.");
+
+        (
+            first,
+            Self {
+                upcoming: scenarios,
+                advancement,
+                stage: 0,
+                stage_generations: 0,
+            },
+        )
+    }
+
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    pub fn stage_count(&self) -> usize {
+        self.advancement.len()
+    }
+
+    /// Counts a generation that has just ended towards the current stage. Returns the next
+    /// stage's scenario if it's time to move on.
+    pub fn next_generation(&mut self, stats: &GenerationStats) -> Option<Scenario> {
+        self.stage_generations += 1;
+
+        let advancement = self.advancement[self.stage].as_ref()?;
+
+        if self.upcoming.is_empty() || !advancement.is_met(self.stage_generations, stats) {
+            return None;
+        }
+
+        self.stage += 1;
+        self.stage_generations = 0;
+        self.upcoming.pop_front()
+    }
+}
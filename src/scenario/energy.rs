@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// How entities spend and regain energy. An entity that runs out of energy dies on the spot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ScenarioEnergy {
+    /// Energy every entity starts the generation with.
+    pub starting: f32,
+    /// Energy can't be stored past this.
+    pub max: f32,
+    /// Spent every step, whether the entity moves or not.
+    #[serde(default)]
+    pub passive_cost: f32,
+    /// Spent for every cell moved.
+    #[serde(default)]
+    pub move_cost: f32,
+    /// Gained for every piece of food eaten, scaled by its nutrition.
+    pub food_value: f32,
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::region::Region;
+
+/// A bonus for entities that end the generation alive inside `region`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FitnessGoal {
+    pub region: Region,
+    pub weight: f32,
+}
+
+/// An entity's score is the sum of each tracked metric multiplied by its weight here. Metrics
+/// left out of a scenario's `fitness` section have no weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Fitness {
+    #[serde(default)]
+    pub times_eaten: f32,
+    #[serde(default)]
+    pub times_irradiated: f32,
+    #[serde(default)]
+    pub distance_travelled: f32,
+    #[serde(default)]
+    pub steps_alive: f32,
+    #[serde(default)]
+    pub checkpoints_reached: f32,
+    #[serde(default)]
+    pub kills: f32,
+    #[serde(default)]
+    pub goal: Option<FitnessGoal>,
+}
+
+impl Default for Fitness {
+    /// Used by scenarios with no `fitness` section.
+    fn default() -> Self {
+        Self {
+            times_eaten: 2.0,
+            times_irradiated: -1.0,
+            distance_travelled: 0.0,
+            steps_alive: 0.0,
+            checkpoints_reached: 0.0,
+            kills: 0.0,
+            goal: None,
+        }
+    }
+}
@@ -1,66 +1,237 @@
-use cellular_automata::grid::grid_coords_to_index;
+use std::{f32::consts::TAU, path::PathBuf};
+
+use cellular_automata::grid::{grid_coords_to_index, grid_index_to_coords};
 use noise::{NoiseFn, Perlin};
-use rand::Rng;
-use serde::Deserialize;
+use rand::{seq::index::sample, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::services::images::GrayImage;
+
+use super::point_index::PointIndex;
+
+/// Which cells food can grow on. Food is placed on `startingFood` of these cells picked at random,
+/// or on all of them if there are fewer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, tag = "kind", rename_all = "camelCase")]
+pub enum FoodDistribution {
+    /// Every cell.
+    Uniform,
+    /// Clusters where Perlin noise is above `threshold`. The noise is reseeded every generation.
+    Perlin {
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        #[serde(default = "default_noise_threshold")]
+        threshold: f64,
+    },
+    /// Cells where a grayscale PNG or PGM image, stretched over the grid, is at least `threshold`
+    /// bright. The path is relative to the scenario file.
+    Mask {
+        path: PathBuf,
+        #[serde(default = "default_mask_threshold")]
+        threshold: u8,
+    },
+    /// Exactly these cells.
+    List { positions: Vec<(u32, u32)> },
+}
+
+fn default_noise_scale() -> f64 {
+    0.05
+}
+
+fn default_noise_threshold() -> f64 {
+    0.3
+}
+
+fn default_mask_threshold() -> u8 {
+    128
+}
+
+impl Default for FoodDistribution {
+    fn default() -> Self {
+        FoodDistribution::Perlin {
+            scale: default_noise_scale(),
+            threshold: default_noise_threshold(),
+        }
+    }
+}
+
+/// Food growing back on fertile cells after it's been eaten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct FoodRegrowth {
+    /// Chance per step that an empty fertile cell grows food.
+    pub rate: f32,
+    pub season: Option<FoodSeason>,
+    /// How far the fertile patches move each step, in cells.
+    #[serde(default)]
+    pub drift: (f32, f32),
+}
 
-#[derive(Deserialize)]
+/// A cycle that speeds regrowth up and slows it down over time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct FoodSeason {
+    /// Steps per full cycle.
+    pub period: u32,
+    /// How far the regrowth rate swings either way, as a fraction of `rate`.
+    pub amplitude: f32,
+}
+
+/// The range each piece of food's nutrition is picked from. Nutrition scales the energy gained
+/// from eating it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodNutrition {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for FoodNutrition {
+    fn default() -> Self {
+        Self { min: 1.0, max: 1.0 }
+    }
+}
+
+impl FoodNutrition {
+    fn roll(&self, rng: &mut impl Rng) -> f32 {
+        if self.max > self.min {
+            rng.gen_range(self.min..=self.max)
+        } else {
+            self.min
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct ScenarioFood {
     pub starting_food: u32,
     pub cull_for_starvation: bool,
+    pub distribution: FoodDistribution,
+    pub regrowth: Option<FoodRegrowth>,
+    pub nutrition: FoodNutrition,
 
-    pub food_map: Vec<bool>,
-    pub food_positions: Vec<(u32, u32)>,
+    /// Whether food can grow on each cell.
+    pub fertile: Vec<bool>,
+    /// The nutrition of the food on each cell, or 0 where there is none.
+    pub food_map: Vec<f32>,
+    pub food_positions: PointIndex,
 }
 
-pub fn generate_food(
-    grid_width: usize,
-    grid_height: usize,
-    starting_food: usize,
-) -> (Vec<bool>, Vec<(u32, u32)>) {
-    let grid_size = grid_width * grid_height;
+/// Works out which cells food can grow on. `mask` must be the image loaded for a `Mask`
+/// distribution.
+pub fn fertile_cells(
+    distribution: &FoodDistribution,
+    mask: Option<&GrayImage>,
+    grid_width: u32,
+    grid_height: u32,
+    rng: &mut impl Rng,
+) -> Vec<bool> {
+    let grid_size = (grid_width * grid_height) as usize;
 
-    let mut food_map = Vec::with_capacity(grid_size);
-    let mut food_positions = Vec::with_capacity(starting_food);
+    match distribution {
+        FoodDistribution::Uniform => vec![true; grid_size],
+        FoodDistribution::Perlin { scale, threshold } => {
+            let perlin = Perlin::new(rng.gen());
 
-    let mut rng = rand::thread_rng();
+            (0..grid_size)
+                .map(|i| {
+                    let (x, y) = grid_index_to_coords(i, grid_width, grid_height);
+                    perlin.get([x as f64 * scale, y as f64 * scale, 0.0]) > *threshold
+                })
+                .collect()
+        }
+        FoodDistribution::Mask { threshold, .. } => {
+            let mask = mask.expect("Mask images should be loaded with the scenario.");
+
+            (0..grid_size)
+                .map(|i| {
+                    let pos = grid_index_to_coords(i, grid_width, grid_height);
+                    mask.sample(pos, grid_width, grid_height) >= *threshold
+                })
+                .collect()
+        }
+        FoodDistribution::List { positions } => {
+            let mut fertile = vec![false; grid_size];
 
-    for _ in 0..grid_size {
-        food_map.push(false);
+            for &(x, y) in positions {
+                if x < grid_width && y < grid_height {
+                    fertile[grid_coords_to_index(x, y, grid_width)] = true;
+                }
+            }
+
+            fertile
+        }
     }
+}
 
-    // TODO: Make the distribution configurable.
-    // Random distribution
-    // for _ in 0..starting_food {
-    //     let mut idx = rand::random::<usize>() % grid_size;
+pub fn generate_food(
+    grid_width: u32,
+    grid_height: u32,
+    starting_food: usize,
+    fertile: &[bool],
+    nutrition: &FoodNutrition,
+    rng: &mut impl Rng,
+) -> (Vec<f32>, PointIndex) {
+    let mut food_map = vec![0.0; fertile.len()];
+    let mut food_positions = PointIndex::new(grid_width, grid_height);
 
-    //     while food_map[idx] {
-    //         idx = rand::random::<usize>() % grid_size;
-    //     }
+    let candidates: Vec<usize> = (0..fertile.len()).filter(|i| fertile[*i]).collect();
+    let count = starting_food.min(candidates.len());
 
-    //     let pos = grid_index_to_coords(idx, grid_width as u32, grid_height as u32);
+    for i in sample(rng, candidates.len(), count) {
+        let idx = candidates[i];
+        food_map[idx] = nutrition.roll(rng);
+        food_positions.insert(grid_index_to_coords(idx, grid_width, grid_height));
+    }
+
+    (food_map, food_positions)
+}
+
+/// Grows food back on empty fertile cells. Fertile patches drift from where they started as the
+/// generation goes on.
+pub fn regrow_food(
+    food: &mut ScenarioFood,
+    grid_width: u32,
+    grid_height: u32,
+    current_step: usize,
+    rng: &mut impl Rng,
+) {
+    let Some(regrowth) = &food.regrowth else {
+        return;
+    };
 
-    //     food_map[idx] = true;
-    //     food_positions.push(pos);
-    // }
+    let season = regrowth.season.as_ref().map_or(1.0, |season| {
+        let phase = current_step as f32 / season.period.max(1) as f32;
+        1.0 + season.amplitude * (phase * TAU).sin()
+    });
+    let rate = (regrowth.rate * season).clamp(0.0, 1.0);
 
-    // Clustered distribution
-    let noise_scale = 0.05;
-    // TODO: Why does threshold cause crashes below .5?
-    let threshold = 0.3;
+    if rate <= 0.0 {
+        return;
+    }
 
-    let seed: u32 = rng.gen();
-    let perlin = Perlin::new(seed);
+    let offset_x = (regrowth.drift.0 * current_step as f32).round() as i64;
+    let offset_y = (regrowth.drift.1 * current_step as f32).round() as i64;
 
     for y in 0..grid_height {
         for x in 0..grid_width {
-            let idx = grid_coords_to_index(x as u32, y as u32, grid_width as u32);
-            let noise_value = perlin.get([x as f64 * noise_scale, y as f64 * noise_scale, 0.0]);
-            if noise_value > threshold {
-                food_map[idx] = true;
-                food_positions.push((x as u32, y as u32));
+            let idx = grid_coords_to_index(x, y, grid_width);
+
+            // Rolling first skips the fertility lookup for most cells.
+            if food.food_map[idx] > 0.0 || rng.gen::<f32>() >= rate {
+                continue;
+            }
+
+            let (source_x, source_y) = (x as i64 - offset_x, y as i64 - offset_y);
+            let in_grid = (0..grid_width as i64).contains(&source_x)
+                && (0..grid_height as i64).contains(&source_y);
+
+            if in_grid
+                && food.fertile[grid_coords_to_index(source_x as u32, source_y as u32, grid_width)]
+            {
+                food.food_map[idx] = food.nutrition.roll(rng);
+                food.food_positions.insert((x, y));
             }
         }
     }
-
-    (food_map, food_positions)
 }
@@ -1,3 +1,13 @@
-mod food;
+pub mod curriculum;
+pub mod energy;
+pub mod fitness;
+pub mod food;
+pub mod movement;
+pub mod obstacles;
+pub mod point_index;
 pub mod radiation_zone;
+pub mod region;
 pub mod scenario;
+pub mod shape;
+pub mod species;
+pub mod survival;
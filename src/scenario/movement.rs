@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{entity::Entity, occupancy::OccupancyGrid};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, tag = "kind", rename_all = "camelCase")]
+pub enum MovementPolicy {
+    /// Any number of entities can share a cell.
+    #[default]
+    Stack,
+    /// Entities move one at a time, and can't move into a cell that's occupied at that point.
+    Block,
+    /// Entities move at the same time, and only into cells that were empty at the start of the
+    /// step. When several entities want the same cell, the one with the highest priority gets it.
+    Priority {
+        #[serde(default)]
+        by: PriorityRule,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriorityRule {
+    /// Every contender is equally likely to win.
+    #[default]
+    Random,
+    /// The contender that has eaten the most wins. Ties are settled at random.
+    TimesEaten,
+}
+
+/// Moves every entity that has a target towards it, following `policy`. Entities that wanted to
+/// move but couldn't are marked as blocked. `occupancy` must hold the positions of all living
+/// entities, and is kept up to date.
+pub fn resolve_moves(
+    policy: MovementPolicy,
+    entities: &mut [Entity],
+    targets: &[Option<(u32, u32)>],
+    occupancy: &mut OccupancyGrid,
+) {
+    match policy {
+        MovementPolicy::Stack => {
+            for (entity, target) in entities.iter_mut().zip(targets) {
+                if let Some(target) = target {
+                    move_entity(entity, *target, occupancy);
+                }
+            }
+        }
+        MovementPolicy::Block => {
+            for (entity, target) in entities.iter_mut().zip(targets) {
+                let Some(target) = *target else {
+                    continue;
+                };
+
+                let position = (entity.body.x, entity.body.y);
+                entity.body.blocked = target != position && occupancy.is_occupied(target);
+
+                if !entity.body.blocked {
+                    move_entity(entity, target, occupancy);
+                }
+            }
+        }
+        MovementPolicy::Priority { by } => {
+            // Contenders for each free cell, keyed by cell so winners are picked in a fixed order.
+            let mut claims: BTreeMap<(u32, u32), Vec<usize>> = BTreeMap::new();
+
+            for (i, (entity, target)) in entities.iter_mut().zip(targets).enumerate() {
+                let Some(target) = *target else {
+                    continue;
+                };
+
+                let position = (entity.body.x, entity.body.y);
+                entity.body.blocked = target != position && occupancy.is_occupied(target);
+
+                if target != position && !entity.body.blocked {
+                    claims.entry(target).or_default().push(i);
+                }
+            }
+
+            for (target, contenders) in claims {
+                let winner = *contenders
+                    .iter()
+                    .max_by_key(|i| {
+                        let entity = &mut entities[**i];
+                        let roll = entity.rng.gen::<u32>();
+
+                        match by {
+                            PriorityRule::Random => (0, roll),
+                            PriorityRule::TimesEaten => (entity.times_eaten, roll),
+                        }
+                    })
+                    .unwrap();
+
+                for i in contenders {
+                    if i == winner {
+                        move_entity(&mut entities[i], target, occupancy);
+                    } else {
+                        entities[i].body.blocked = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn move_entity(entity: &mut Entity, target: (u32, u32), occupancy: &mut OccupancyGrid) {
+    let position = (entity.body.x, entity.body.y);
+
+    if target == position {
+        return;
+    }
+
+    occupancy.remove(position);
+    occupancy.add(target);
+
+    entity.body.x = target.0;
+    entity.body.y = target.1;
+    entity.distance_travelled += 1;
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use cellular_automata::grid::{grid_coords_to_index, grid_index_to_coords};
+use serde::{Deserialize, Serialize};
+
+use crate::services::images::GrayImage;
+
+use super::region::Region;
+
+/// A grayscale PNG or PGM image stretched over the grid. Cells at least `threshold` bright are
+/// walls. The path is relative to the scenario file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObstacleMask {
+    pub path: PathBuf,
+    #[serde(default = "default_mask_threshold")]
+    pub threshold: u8,
+}
+
+fn default_mask_threshold() -> u8 {
+    128
+}
+
+/// Impassable cells.
+#[derive(Serialize, Deserialize)]
+pub struct Obstacles {
+    grid_width: u32,
+    walls: Vec<bool>,
+}
+
+impl Obstacles {
+    /// Builds the walls from rectangles and an optional mask image, which must be the image
+    /// loaded for `mask`.
+    pub fn new(
+        rects: &[Region],
+        mask: Option<(&ObstacleMask, &GrayImage)>,
+        grid_width: u32,
+        grid_height: u32,
+    ) -> Self {
+        let walls = (0..(grid_width * grid_height) as usize)
+            .map(|i| {
+                let pos = grid_index_to_coords(i, grid_width, grid_height);
+
+                rects.iter().any(|rect| rect.contains(pos))
+                    || mask.is_some_and(|(mask, image)| {
+                        image.sample(pos, grid_width, grid_height) >= mask.threshold
+                    })
+            })
+            .collect();
+
+        Self { grid_width, walls }
+    }
+
+    pub fn is_wall(&self, (x, y): (u32, u32)) -> bool {
+        self.is_wall_at_index(grid_coords_to_index(x, y, self.grid_width))
+    }
+
+    pub fn is_wall_at_index(&self, index: usize) -> bool {
+        self.walls[index]
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// Side length, in grid cells, of each bucket of the index.
+const BUCKET_SIZE: u32 = 8;
+
+/// A set of grid positions bucketed into a uniform grid, so the nearest position to a point can
+/// be found by only looking at the buckets around it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointIndex {
+    buckets_wide: u32,
+    buckets_high: u32,
+    buckets: Vec<Vec<(u32, u32)>>,
+    len: usize,
+}
+
+impl PointIndex {
+    pub fn new(grid_width: u32, grid_height: u32) -> Self {
+        let buckets_wide = grid_width.div_ceil(BUCKET_SIZE).max(1);
+        let buckets_high = grid_height.div_ceil(BUCKET_SIZE).max(1);
+
+        Self {
+            buckets_wide,
+            buckets_high,
+            buckets: vec![Vec::new(); (buckets_wide * buckets_high) as usize],
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, point: (u32, u32)) {
+        let bucket = self.bucket_index(point);
+        self.buckets[bucket].push(point);
+        self.len += 1;
+    }
+
+    /// Removes `point` from the index. Returns false if it wasn't there.
+    pub fn remove(&mut self, point: (u32, u32)) -> bool {
+        let index = self.bucket_index(point);
+        let bucket = &mut self.buckets[index];
+
+        match bucket.iter().position(|p| *p == point) {
+            Some(i) => {
+                bucket.swap_remove(i);
+                self.len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finds the position closest to `(x, y)`. Ties are broken by row and then column, so the
+    /// result doesn't depend on the order positions were inserted or removed in.
+    pub fn nearest(&self, (x, y): (u32, u32)) -> Option<(u32, u32)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (bx, by) = (x / BUCKET_SIZE, y / BUCKET_SIZE);
+        let max_ring = self.buckets_wide.max(self.buckets_high);
+
+        let mut best: Option<(u64, (u32, u32))> = None;
+
+        for ring in 0..=max_ring {
+            // Anything in this ring or further out is at least this far away on one axis.
+            if let Some((best_dist, _)) = best {
+                let min_offset = (ring.saturating_sub(1) * BUCKET_SIZE + 1) as u64;
+                if ring > 0 && min_offset * min_offset > best_dist {
+                    break;
+                }
+            }
+
+            for bucket in self.ring_buckets(bx, by, ring) {
+                for &(px, py) in &self.buckets[bucket] {
+                    let dx = px.abs_diff(x) as u64;
+                    let dy = py.abs_diff(y) as u64;
+                    let dist = dx * dx + dy * dy;
+
+                    let closer = match best {
+                        None => true,
+                        Some((best_dist, (best_x, best_y))) => {
+                            (dist, py, px) < (best_dist, best_y, best_x)
+                        }
+                    };
+
+                    if closer {
+                        best = Some((dist, (px, py)));
+                    }
+                }
+            }
+        }
+
+        best.map(|(_, point)| point)
+    }
+
+    fn bucket_index(&self, (x, y): (u32, u32)) -> usize {
+        let bx = (x / BUCKET_SIZE).min(self.buckets_wide - 1);
+        let by = (y / BUCKET_SIZE).min(self.buckets_high - 1);
+
+        (by * self.buckets_wide + bx) as usize
+    }
+
+    /// Indices of the buckets exactly `ring` buckets away from `(bx, by)`, clipped to the grid.
+    fn ring_buckets(&self, bx: u32, by: u32, ring: u32) -> Vec<usize> {
+        let (bx, by, ring) = (bx as i64, by as i64, ring as i64);
+        let (wide, high) = (self.buckets_wide as i64, self.buckets_high as i64);
+
+        let mut buckets = Vec::new();
+        let mut push = |cx: i64, cy: i64| {
+            if cx >= 0 && cx < wide && cy >= 0 && cy < high {
+                buckets.push((cy * wide + cx) as usize);
+            }
+        };
+
+        for cy in (by - ring).max(0)..=(by + ring).min(high - 1) {
+            if cy == by - ring || cy == by + ring {
+                for cx in (bx - ring).max(0)..=(bx + ring).min(wide - 1) {
+                    push(cx, cy);
+                }
+            } else {
+                push(bx - ring, cy);
+                push(bx + ring, cy);
+            }
+        }
+
+        buckets
+    }
+}
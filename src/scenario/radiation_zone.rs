@@ -1,19 +1,206 @@
-use serde::Deserialize;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use crate::vector_2d::Vector2D;
+
+use super::shape::Shape;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RadiationZone {
     pub start_time: usize,
     pub end_time: usize,
-    pub position: (u32, u32),
-    pub width: u32,
-    pub height: u32,
+    pub position: (f32, f32),
+    #[serde(flatten)]
+    pub shape: Shape,
+
+    /// Where the zone is and how big it is at given steps. The zone moves and grows in a straight
+    /// line between keyframes, and holds still before the first and after the last.
+    #[serde(default)]
+    pub path: Vec<ZoneKeyframe>,
+
+    /// How strong the radiation is at the middle of the zone.
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+    /// How strong the radiation is at the zone's furthest edge. Matches `intensity` when absent.
+    pub edge_intensity: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ZoneKeyframe {
+    pub time: usize,
+    /// Keeps the zone's `position` when absent.
+    pub position: Option<(f32, f32)>,
+    #[serde(default = "default_scale")]
+    pub scale: ZoneScale,
+}
+
+/// How much a zone is stretched, given as one number to stretch it evenly or as `[x, y]` to
+/// stretch each axis by its own amount. Circles can only be stretched evenly.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ZoneScale {
+    Even(f32),
+    Axes(f32, f32),
+}
+
+impl ZoneScale {
+    pub fn axes(self) -> (f32, f32) {
+        match self {
+            ZoneScale::Even(scale) => (scale, scale),
+            ZoneScale::Axes(x, y) => (x, y),
+        }
+    }
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+fn default_scale() -> ZoneScale {
+    ZoneScale::Even(1.0)
+}
+
+impl RadiationZone {
+    pub fn is_active(&self, step: usize) -> bool {
+        step >= self.start_time && step <= self.end_time
+    }
+
+    /// The zone's origin and scale at `step`.
+    fn placement(&self, step: usize) -> ((f32, f32), (f32, f32)) {
+        let keyframe = |k: &ZoneKeyframe| (k.position.unwrap_or(self.position), k.scale.axes());
+
+        let Some(next) = self.path.iter().position(|k| k.time > step) else {
+            return self
+                .path
+                .last()
+                .map_or((self.position, (1.0, 1.0)), keyframe);
+        };
+
+        if next == 0 {
+            return keyframe(&self.path[0]);
+        }
+
+        let (from, to) = (&self.path[next - 1], &self.path[next]);
+        let t = (step - from.time) as f32 / (to.time - from.time) as f32;
+        let ((fx, fy), (from_sx, from_sy)) = keyframe(from);
+        let ((tx, ty), (to_sx, to_sy)) = keyframe(to);
+
+        (
+            (fx + (tx - fx) * t, fy + (ty - fy) * t),
+            (
+                from_sx + (to_sx - from_sx) * t,
+                from_sy + (to_sy - from_sy) * t,
+            ),
+        )
+    }
+
+    pub fn contains(&self, (x, y): (u32, u32), step: usize) -> bool {
+        let (origin, scale) = self.placement(step);
+        self.shape.contains(origin, scale, (x as f32, y as f32))
+    }
+
+    /// The shortest vector from a cell to the zone, or zero if the cell is inside it.
+    pub fn displacement(&self, (x, y): (u32, u32), step: usize) -> Vector2D {
+        let (origin, scale) = self.placement(step);
+        self.shape.displacement(origin, scale, (x as f32, y as f32))
+    }
+
+    /// A circle around the zone at `step`, as its centre and radius. Nothing in the zone is further
+    /// out than that.
+    pub fn bounds(&self, step: usize) -> ((f32, f32), f32) {
+        let (origin, scale) = self.placement(step);
+        self.shape.centre_and_reach(origin, scale)
+    }
+
+    /// How strong the radiation is on a cell, or 0 if the cell is outside the zone.
+    pub fn intensity_at(&self, point: (u32, u32), step: usize) -> f32 {
+        if !self.contains(point, step) {
+            return 0.0;
+        }
+
+        let Some(edge_intensity) = self.edge_intensity else {
+            return self.intensity;
+        };
+
+        let (origin, scale) = self.placement(step);
+        let (centre, reach) = self.shape.centre_and_reach(origin, scale);
+        let distance = Vector2D {
+            x: point.0 as f32 - centre.0,
+            y: point.1 as f32 - centre.1,
+        }
+        .magnitude();
+        let t = if reach > 0.0 {
+            (distance / reach).min(1.0)
+        } else {
+            0.0
+        };
+
+        self.intensity + (edge_intensity - self.intensity) * t
+    }
+}
+
+/// When a radiation dose kills.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, tag = "kind", rename_all = "camelCase")]
+pub enum DoseDeath {
+    /// Death as soon as the dose reaches `dose`.
+    Threshold { dose: f32 },
+    /// A chance of death every step, which is 50% at `dose` and rises more sharply around it the
+    /// higher `steepness` is.
+    Logistic { dose: f32, steepness: f32 },
+}
+
+impl DoseDeath {
+    pub fn is_fatal(&self, current_dose: f32, rng: &mut impl Rng) -> bool {
+        if current_dose <= 0.0 {
+            return false;
+        }
+
+        match self {
+            DoseDeath::Threshold { dose } => current_dose >= *dose,
+            DoseDeath::Logistic { dose, steepness } => {
+                let chance = 1.0 / (1.0 + (-steepness * (current_dose - dose)).exp());
+                rng.gen::<f32>() < chance
+            }
+        }
+    }
+}
+
+/// Score lost every step, as `weight * dose ^ exponent` for the entity's current dose.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DosePenalty {
+    pub weight: f32,
+    #[serde(default = "default_exponent")]
+    pub exponent: f32,
+}
+
+fn default_exponent() -> f32 {
+    1.0
+}
+
+impl DosePenalty {
+    pub fn at(&self, dose: f32) -> f32 {
+        if dose > 0.0 {
+            self.weight * dose.powf(self.exponent)
+        } else {
+            0.0
+        }
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ScenarioRadiation {
-    pub death_threshold: Option<u32>,
+    /// The fraction of its dose an entity recovers from every step.
+    pub recovery: f32,
+    pub death: Option<DoseDeath>,
+    pub penalty: Option<DosePenalty>,
     pub starting_rad_zones: Vec<RadiationZone>,
     pub remaining_rad_zones: Vec<usize>,
     pub active_rad_zones: Vec<usize>,
+    /// The bounds of each active zone on the current step, in the same order.
+    #[serde(skip)]
+    pub active_zone_bounds: Vec<((f32, f32), f32)>,
 }
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// An axis-aligned rectangle of grid cells.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub position: (u32, u32),
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn contains(&self, (x, y): (u32, u32)) -> bool {
+        x >= self.position.0
+            && x < self.position.0 + self.width
+            && y >= self.position.1
+            && y < self.position.1 + self.height
+    }
+}
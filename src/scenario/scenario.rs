@@ -1,69 +1,158 @@
-use cellular_automata::grid::grid_coords_to_index;
-use serde::Deserialize;
+use std::mem;
 
-use crate::{services::scenarios::ScenarioFile, vector_2d::Vector2D};
+use cellular_automata::grid::grid_coords_to_index;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entity::Entity,
+    genome::crossover::CrossoverStrategy,
+    selection::strategy::SelectionStrategy,
+    services::scenarios::{FoodFile, RadiationFile, ScenarioFile},
+    vector_2d::Vector2D,
+};
 
 use super::{
-    food::{generate_food, ScenarioFood},
-    radiation_zone::ScenarioRadiation,
+    energy::ScenarioEnergy,
+    fitness::Fitness,
+    food::{fertile_cells, generate_food, regrow_food, FoodDistribution, ScenarioFood},
+    movement::MovementPolicy,
+    obstacles::Obstacles,
+    radiation_zone::{DoseDeath, ScenarioRadiation},
+    species::{species_counts, Species},
+    survival::Survival,
 };
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Scenario {
+    pub name: String,
     pub generation_step_count: usize,
 
     pub supplement_population: bool,
     pub limit_population: bool,
+    pub crossover: Option<CrossoverStrategy>,
+    pub selection: SelectionStrategy,
+    pub fitness: Fitness,
+    pub survival: Survival,
+    /// The species entities are split into. Everyone belongs to one implicit species when empty.
+    pub species: Vec<Species>,
+    pub movement: MovementPolicy,
+    pub energy: Option<ScenarioEnergy>,
+    pub obstacles: Option<Obstacles>,
 
     pub radiation: Option<ScenarioRadiation>,
 
     pub food: Option<ScenarioFood>,
 
+    /// Every phase, in order. The current phase's radiation, food and fitness are held in the
+    /// fields above, and its entry here is left empty until the next phase starts.
+    pub phases: Vec<ScenarioPhase>,
+    pub current_phase: usize,
+
     grid_width: u32,
     grid_height: u32,
 }
 
-impl Scenario {
-    pub fn from_file(config: ScenarioFile, grid_width: u32, grid_height: u32) -> Self {
-        let mut generation_step_count = 0;
+/// A stretch of the generation with its own radiation, food and fitness.
+#[derive(Serialize, Deserialize)]
+pub struct ScenarioPhase {
+    pub steps: usize,
+    pub radiation: Option<ScenarioRadiation>,
+    pub food: Option<ScenarioFood>,
+    pub fitness: Fitness,
+}
 
-        let radiation = if let Some(radiation_config) = config.radiation {
-            let starting_rad_zones = radiation_config.zones;
+impl Scenario {
+    pub fn from_file(
+        config: ScenarioFile,
+        grid_width: u32,
+        grid_height: u32,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let obstacles = config.obstacles.map(|obstacles_config| {
+            Obstacles::new(
+                &obstacles_config.rects,
+                obstacles_config
+                    .mask
+                    .as_ref()
+                    .zip(obstacles_config.mask_image.as_ref()),
+                grid_width,
+                grid_height,
+            )
+        });
+
+        let mut phases = Vec::with_capacity(config.phases.len());
+        let mut phase_start = 0;
+
+        for phase_config in config.phases {
+            let radiation = phase_config
+                .radiation
+                .or_else(|| config.radiation.clone())
+                .map(|radiation_config| build_radiation(radiation_config, phase_start));
+            let food = phase_config
+                .food
+                .or_else(|| config.food.clone())
+                .map(|food_config| {
+                    build_food(
+                        food_config,
+                        obstacles.as_ref(),
+                        grid_width,
+                        grid_height,
+                        rng,
+                    )
+                });
+
+            phases.push(ScenarioPhase {
+                steps: phase_config.steps,
+                fitness: phase_config
+                    .fitness
+                    .unwrap_or_else(|| config.fitness.clone()),
+                radiation,
+                food,
+            });
+
+            phase_start += phase_config.steps;
+        }
 
-            generation_step_count = starting_rad_zones
-                .iter()
-                .map(|kz| kz.end_time)
-                .max()
-                .unwrap();
-
-            Some(ScenarioRadiation {
-                death_threshold: radiation_config.death_threshold,
-                remaining_rad_zones: (0..starting_rad_zones.len()).collect(),
-                starting_rad_zones,
-                active_rad_zones: Vec::new(),
-            })
+        let (radiation, food, fitness) = if let Some(first) = phases.first_mut() {
+            (
+                first.radiation.take(),
+                first.food.take(),
+                first.fitness.clone(),
+            )
         } else {
-            None
+            (
+                config
+                    .radiation
+                    .map(|radiation_config| build_radiation(radiation_config, 0)),
+                config.food.map(|food_config| {
+                    build_food(
+                        food_config,
+                        obstacles.as_ref(),
+                        grid_width,
+                        grid_height,
+                        rng,
+                    )
+                }),
+                config.fitness,
+            )
         };
 
-        let food = if let Some(food_config) = config.food {
-            let (food_map, food_positions) = generate_food(
-                grid_width as usize,
-                grid_height as usize,
-                food_config.starting_food as usize,
-            );
-
-            Some(ScenarioFood {
-                starting_food: food_config.starting_food,
-                cull_for_starvation: food_config.cull_for_starvation,
-                food_map,
-                food_positions,
-            })
-        } else {
-            None
-        };
+        let generation_step_count = config.generation_steps.unwrap_or_else(|| {
+            if phases.is_empty() {
+                radiation
+                    .iter()
+                    .flat_map(|radiation| &radiation.starting_rad_zones)
+                    .map(|zone| zone.end_time)
+                    .max()
+                    .unwrap_or_default()
+            } else {
+                phase_start
+            }
+        });
 
         Self {
+            name: config.name,
             generation_step_count,
             radiation,
             food,
@@ -71,20 +160,102 @@ impl Scenario {
             grid_height,
             supplement_population: config.supplement_population,
             limit_population: config.limit_population,
+            crossover: config.crossover,
+            selection: config.selection,
+            fitness,
+            survival: config.survival,
+            species: config.species,
+            movement: config.movement,
+            energy: config.energy,
+            obstacles,
+            phases,
+            current_phase: 0,
         }
     }
 
-    pub fn reset(&mut self) {
+    pub fn grid_size(&self) -> (u32, u32) {
+        (self.grid_width, self.grid_height)
+    }
+
+    /// How many of `total` entities belong to each species.
+    pub fn species_counts(&self, total: u32) -> Vec<u32> {
+        species_counts(&self.species, total)
+    }
+
+    /// The index of the species an entity belongs to in this scenario. Entities tagged with a
+    /// species the scenario doesn't have count as the first species.
+    pub fn species_of(&self, entity: &Entity) -> usize {
+        if entity.species < self.species.len() {
+            entity.species
+        } else {
+            0
+        }
+    }
+
+    /// The fitness entities of a species are scored with.
+    pub fn fitness_for(&self, species: usize) -> &Fitness {
+        self.species
+            .get(species)
+            .and_then(|species| species.fitness.as_ref())
+            .unwrap_or(&self.fitness)
+    }
+
+    /// Whether a species is scored by its own fitness rather than the scenario's.
+    pub fn has_own_fitness(&self, species: usize) -> bool {
+        self.species
+            .get(species)
+            .is_some_and(|species| species.fitness.is_some())
+    }
+
+    pub fn has_hunters(&self) -> bool {
+        self.species
+            .iter()
+            .any(|species| !species.preys_on.is_empty())
+    }
+
+    pub fn hunts(&self, hunter: usize, prey: usize) -> bool {
+        match (self.species.get(hunter), self.species.get(prey)) {
+            (Some(hunter), Some(prey)) => hunter.preys_on.contains(&prey.name),
+            _ => false,
+        }
+    }
+
+    pub fn reset(&mut self, rng: &mut impl Rng) {
+        if self.current_phase != 0 {
+            self.enter_phase(0);
+        }
+
+        self.reset_phase(rng);
+    }
+
+    /// Puts the current phase's radiation zones and food back how they were at its start.
+    fn reset_phase(&mut self, rng: &mut impl Rng) {
         if let Some(radiation) = &mut self.radiation {
             radiation.remaining_rad_zones = (0..radiation.starting_rad_zones.len()).collect();
             radiation.active_rad_zones = Vec::new();
+            radiation.active_zone_bounds = Vec::new();
         }
 
         if let Some(food) = &mut self.food {
+            // Noise is the only distribution that changes between generations.
+            if let FoodDistribution::Perlin { .. } = food.distribution {
+                food.fertile = fertile_cells(
+                    &food.distribution,
+                    None,
+                    self.grid_width,
+                    self.grid_height,
+                    rng,
+                );
+                clear_walls(&mut food.fertile, self.obstacles.as_ref());
+            }
+
             let (food_map, food_positions) = generate_food(
-                self.grid_width as usize,
-                self.grid_height as usize,
+                self.grid_width,
+                self.grid_height,
                 food.starting_food as usize,
+                &food.fertile,
+                &food.nutrition,
+                rng,
             );
 
             food.food_map = food_map;
@@ -92,10 +263,41 @@ impl Scenario {
         }
     }
 
-    pub fn update(&mut self, current_step: usize) {
+    /// Stores the current phase's state and swaps in `phase`'s.
+    fn enter_phase(&mut self, phase: usize) {
+        let current = &mut self.phases[self.current_phase];
+        mem::swap(&mut self.radiation, &mut current.radiation);
+        mem::swap(&mut self.food, &mut current.food);
+        mem::swap(&mut self.fitness, &mut current.fitness);
+
+        let next = &mut self.phases[phase];
+        mem::swap(&mut self.radiation, &mut next.radiation);
+        mem::swap(&mut self.food, &mut next.food);
+        mem::swap(&mut self.fitness, &mut next.fitness);
+
+        self.current_phase = phase;
+    }
+
+    /// Advances the scenario to `current_step`. When that starts a new phase, the fitness of the
+    /// phase that just ended is returned, so scores earned under it can be kept.
+    pub fn update(&mut self, current_step: usize, rng: &mut impl Rng) -> Option<Fitness> {
         // TODO: We can know how many steps away the next need for an update is. We can skip
         // updating until that point.
 
+        let phase_end: usize = self
+            .phases
+            .iter()
+            .take(self.current_phase + 1)
+            .map(|phase| phase.steps)
+            .sum();
+        let mut ended_fitness = None;
+
+        if self.current_phase + 1 < self.phases.len() && current_step >= phase_end {
+            ended_fitness = Some(self.fitness.clone());
+            self.enter_phase(self.current_phase + 1);
+            self.reset_phase(rng);
+        }
+
         if let Some(radiation) = &mut self.radiation {
             radiation
                 .remaining_rad_zones
@@ -106,46 +308,54 @@ impl Scenario {
                     .remaining_rad_zones
                     .iter()
                     .fold(Vec::new(), |mut acc, kzi| {
-                        let kz = &radiation.starting_rad_zones[*kzi];
-                        if current_step >= kz.start_time && current_step <= kz.end_time {
+                        if radiation.starting_rad_zones[*kzi].is_active(current_step) {
                             acc.push(*kzi);
                         }
 
                         acc
                     });
+            radiation.active_zone_bounds = radiation
+                .active_rad_zones
+                .iter()
+                .map(|i| radiation.starting_rad_zones[*i].bounds(current_step))
+                .collect();
         }
+
+        if let Some(food) = &mut self.food {
+            regrow_food(food, self.grid_width, self.grid_height, current_step, rng);
+        }
+
+        ended_fitness
     }
 
-    pub fn shortest_rad_zone_displacement(&self, (x, y): (u32, u32)) -> (f32, Vector2D) {
+    /// Finds the nearest active radiation zone. Zones whose bounds are no closer than the nearest
+    /// zone found so far are skipped without working out their exact displacement.
+    pub fn shortest_rad_zone_displacement(
+        &self,
+        point: (u32, u32),
+        current_step: usize,
+    ) -> (f32, Vector2D) {
         let mut min_dist = f32::MAX;
         let mut min_disp = Vector2D { x: 0.0, y: 0.0 };
 
-        let radiation = self.radiation.as_ref().unwrap();
-
-        for i in &radiation.active_rad_zones {
-            let kz = &radiation.starting_rad_zones[*i];
+        let Some(radiation) = self.radiation.as_ref() else {
+            return (min_dist, min_disp);
+        };
 
-            let dx: i32 = if x < kz.position.0 {
-                kz.position.0 as i32 - x as i32
-            } else if x >= kz.position.0 + kz.width {
-                (kz.position.0 + kz.width) as i32 - x as i32
-            } else {
-                0
-            };
+        let (px, py) = (point.0 as f32, point.1 as f32);
 
-            let dy: i32 = if y < kz.position.1 {
-                kz.position.1 as i32 - y as i32
-            } else if y >= kz.position.1 + kz.height {
-                (kz.position.1 + kz.height) as i32 - y as i32
-            } else {
-                0
-            };
+        for (i, ((cx, cy), reach)) in radiation
+            .active_rad_zones
+            .iter()
+            .zip(&radiation.active_zone_bounds)
+        {
+            let closest_possible = ((px - cx).hypot(py - cy) - reach).max(0.0);
 
-            let disp = Vector2D {
-                x: dx as f32,
-                y: dy as f32,
-            };
+            if closest_possible >= min_dist {
+                continue;
+            }
 
+            let disp = radiation.starting_rad_zones[*i].displacement(point, current_step);
             let dist = disp.magnitude();
 
             if dist < min_dist {
@@ -158,54 +368,152 @@ impl Scenario {
     }
 
     pub fn shortest_food_displacement(&self, (x, y): (u32, u32)) -> (f32, (i32, i32)) {
-        let mut min_dist = f32::MAX;
-        let mut min_disp = (i32::MAX, i32::MAX);
-
         let food = self.food.as_ref().unwrap();
 
-        for (fx, fy) in &food.food_positions {
-            let dx = *fx as i32 - x as i32;
-            let dy = *fy as i32 - y as i32;
-
-            let vec = Vector2D {
-                x: *fx as f32 - x as f32,
-                y: *fy as f32 - y as f32,
-            };
-
-            let dist = vec.magnitude();
-
-            if dist < min_dist {
-                min_dist = dist;
-                min_disp = (dx, dy);
+        match food.food_positions.nearest((x, y)) {
+            Some((fx, fy)) => {
+                let vec = Vector2D {
+                    x: fx as f32 - x as f32,
+                    y: fy as f32 - y as f32,
+                };
+
+                (
+                    vec.magnitude(),
+                    (fx as i32 - x as i32, fy as i32 - y as i32),
+                )
             }
+            None => (f32::MAX, (i32::MAX, i32::MAX)),
         }
-
-        (min_dist, min_disp)
     }
 
     pub fn is_food_at_point(&self, (x, y): (u32, u32)) -> bool {
         let index = grid_coords_to_index(x, y, self.grid_width);
-        self.food.as_ref().unwrap().food_map[index]
+        self.food.as_ref().unwrap().food_map[index] > 0.0
     }
 
-    pub fn consume_food_at_point(&mut self, (x, y): (u32, u32)) {
+    /// Removes the food at a point and returns its nutrition.
+    pub fn consume_food_at_point(&mut self, (x, y): (u32, u32)) -> f32 {
         let index = grid_coords_to_index(x, y, self.grid_width);
         let food = self.food.as_mut().unwrap();
-        food.food_map[index] = false;
-        food.food_positions.retain(|&(fx, fy)| fx != x || fy != y);
-    }
-
-    pub fn is_point_in_rad_zone(&self, (x, y): (u32, u32), generation_time: usize) -> bool {
-        let radiation = self.radiation.as_ref().unwrap();
-
-        radiation.active_rad_zones.iter().any(|i| {
-            let kz = &radiation.starting_rad_zones[*i];
-            generation_time >= kz.start_time
-                && generation_time <= kz.end_time
-                && x >= kz.position.0
-                && x < kz.position.0 + kz.width
-                && y >= kz.position.1
-                && y < kz.position.1 + kz.height
-        })
+        food.food_positions.remove((x, y));
+        std::mem::take(&mut food.food_map[index])
+    }
+
+    pub fn is_wall(&self, point: (u32, u32)) -> bool {
+        self.obstacles
+            .as_ref()
+            .is_some_and(|obstacles| obstacles.is_wall(point))
+    }
+
+    /// Whether a cell can't be entered, either because it's a wall or because it's off the grid.
+    pub fn is_blocked(&self, (x, y): (i64, i64)) -> bool {
+        x < 0
+            || y < 0
+            || x >= self.grid_width as i64
+            || y >= self.grid_height as i64
+            || self.is_wall((x as u32, y as u32))
+    }
+
+    /// How many cells can be entered in a straight line from `point` in direction `facing`,
+    /// counting up to `range`.
+    pub fn clear_distance(&self, (x, y): (u32, u32), facing: (i32, i32), range: u32) -> u32 {
+        (1..=range)
+            .take_while(|step| {
+                let step = *step as i64;
+                !self.is_blocked((
+                    x as i64 + facing.0 as i64 * step,
+                    y as i64 + facing.1 as i64 * step,
+                ))
+            })
+            .count() as u32
+    }
+
+    /// The strongest radiation on a cell out of every active zone covering it.
+    pub fn rad_intensity_at_point(&self, point: (u32, u32), generation_time: usize) -> f32 {
+        let Some(radiation) = self.radiation.as_ref() else {
+            return 0.0;
+        };
+
+        radiation
+            .active_rad_zones
+            .iter()
+            .map(|i| &radiation.starting_rad_zones[*i])
+            .filter(|kz| kz.is_active(generation_time))
+            .map(|kz| kz.intensity_at(point, generation_time))
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Sets up radiation for a phase starting at step `start`. Zone times in the file count from the
+/// start of the phase.
+fn build_radiation(config: RadiationFile, start: usize) -> ScenarioRadiation {
+    let mut starting_rad_zones = config.zones;
+
+    for zone in &mut starting_rad_zones {
+        zone.start_time += start;
+        zone.end_time += start;
+        zone.path.sort_by_key(|keyframe| keyframe.time);
+
+        for keyframe in &mut zone.path {
+            keyframe.time += start;
+        }
+    }
+
+    ScenarioRadiation {
+        recovery: config.recovery,
+        death: config.death.or(config
+            .death_threshold
+            .map(|dose| DoseDeath::Threshold { dose: dose as f32 })),
+        penalty: config.penalty,
+        remaining_rad_zones: (0..starting_rad_zones.len()).collect(),
+        starting_rad_zones,
+        active_rad_zones: Vec::new(),
+        active_zone_bounds: Vec::new(),
+    }
+}
+
+fn build_food(
+    config: FoodFile,
+    obstacles: Option<&Obstacles>,
+    grid_width: u32,
+    grid_height: u32,
+    rng: &mut impl Rng,
+) -> ScenarioFood {
+    let mut fertile = fertile_cells(
+        &config.distribution,
+        config.mask.as_ref(),
+        grid_width,
+        grid_height,
+        rng,
+    );
+    clear_walls(&mut fertile, obstacles);
+
+    let (food_map, food_positions) = generate_food(
+        grid_width,
+        grid_height,
+        config.starting_food as usize,
+        &fertile,
+        &config.nutrition,
+        rng,
+    );
+
+    ScenarioFood {
+        starting_food: config.starting_food,
+        cull_for_starvation: config.cull_for_starvation,
+        distribution: config.distribution,
+        regrowth: config.regrowth,
+        nutrition: config.nutrition,
+        fertile,
+        food_map,
+        food_positions,
+    }
+}
+
+/// Stops food growing inside walls.
+fn clear_walls(fertile: &mut [bool], obstacles: Option<&Obstacles>) {
+    if let Some(obstacles) = obstacles {
+        for (i, cell) in fertile.iter_mut().enumerate() {
+            *cell &= !obstacles.is_wall_at_index(i);
+        }
     }
 }
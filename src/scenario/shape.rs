@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vector_2d::Vector2D;
+
+/// The outline of an area on the grid, relative to an origin. Which kind it is follows from the
+/// fields given, so `width` and `height` alone still describe a rectangle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    untagged,
+    deny_unknown_fields,
+    expecting = "`width` and `height`, `radius` or `points`, and no unknown fields"
+)]
+pub enum Shape {
+    /// The origin is the top-left corner.
+    Rect { width: f32, height: f32 },
+    /// The origin is the centre.
+    Circle { radius: f32 },
+    /// Vertices, in order, relative to the origin.
+    Polygon { points: Vec<(f32, f32)> },
+}
+
+impl Shape {
+    /// Whether cell `point` is inside the shape when placed at `origin` and scaled by `scale` along
+    /// each axis. Circles are scaled by the first of the two.
+    pub fn contains(&self, origin: (f32, f32), scale: (f32, f32), point: (f32, f32)) -> bool {
+        let (x, y) = point;
+
+        match self {
+            Shape::Rect { width, height } => {
+                x >= origin.0
+                    && x < origin.0 + width * scale.0
+                    && y >= origin.1
+                    && y < origin.1 + height * scale.1
+            }
+            Shape::Circle { radius } => {
+                let (dx, dy) = (x - origin.0, y - origin.1);
+                dx * dx + dy * dy <= (radius * scale.0).powi(2)
+            }
+            Shape::Polygon { points } => {
+                let vertices = polygon_vertices(points, origin, scale);
+                let mut inside = false;
+
+                // Count how many edges a ray cast to the right of the point crosses.
+                for i in 0..vertices.len() {
+                    let (ax, ay) = vertices[i];
+                    let (bx, by) = vertices[(i + 1) % vertices.len()];
+
+                    if (ay > y) != (by > y) && x < ax + (y - ay) / (by - ay) * (bx - ax) {
+                        inside = !inside;
+                    }
+                }
+
+                inside
+            }
+        }
+    }
+
+    /// The shortest vector from `point` to the shape, or zero if the point is inside it.
+    pub fn displacement(
+        &self,
+        origin: (f32, f32),
+        scale: (f32, f32),
+        point: (f32, f32),
+    ) -> Vector2D {
+        let zero = Vector2D { x: 0.0, y: 0.0 };
+
+        if self.contains(origin, scale, point) {
+            return zero;
+        }
+
+        let (x, y) = point;
+
+        match self {
+            Shape::Rect { width, height } => {
+                let (right, bottom) = (origin.0 + width * scale.0, origin.1 + height * scale.1);
+
+                let dx = if x < origin.0 {
+                    origin.0 - x
+                } else if x >= right {
+                    right - x
+                } else {
+                    0.0
+                };
+
+                let dy = if y < origin.1 {
+                    origin.1 - y
+                } else if y >= bottom {
+                    bottom - y
+                } else {
+                    0.0
+                };
+
+                Vector2D { x: dx, y: dy }
+            }
+            Shape::Circle { radius } => {
+                let to_centre = Vector2D {
+                    x: origin.0 - x,
+                    y: origin.1 - y,
+                };
+                let gap = to_centre.magnitude() - radius * scale.0;
+                let dir = to_centre.normalize();
+
+                Vector2D {
+                    x: dir.x * gap,
+                    y: dir.y * gap,
+                }
+            }
+            Shape::Polygon { points } => {
+                let vertices = polygon_vertices(points, origin, scale);
+                let mut closest: Option<Vector2D> = None;
+
+                for i in 0..vertices.len() {
+                    let disp = segment_displacement(
+                        point,
+                        vertices[i],
+                        vertices[(i + 1) % vertices.len()],
+                    );
+
+                    if closest.is_none_or(|c| disp.magnitude() < c.magnitude()) {
+                        closest = Some(disp);
+                    }
+                }
+
+                closest.unwrap_or(zero)
+            }
+        }
+    }
+
+    /// The middle of the shape, and how far its furthest edge is from there.
+    pub fn centre_and_reach(&self, origin: (f32, f32), scale: (f32, f32)) -> ((f32, f32), f32) {
+        match self {
+            Shape::Rect { width, height } => {
+                let (half_w, half_h) = (width * scale.0 / 2.0, height * scale.1 / 2.0);
+                let reach = (half_w * half_w + half_h * half_h).sqrt();
+
+                ((origin.0 + half_w, origin.1 + half_h), reach)
+            }
+            Shape::Circle { radius } => (origin, radius * scale.0),
+            Shape::Polygon { points } => {
+                let vertices = polygon_vertices(points, origin, scale);
+                let count = vertices.len().max(1) as f32;
+                let centre = (
+                    vertices.iter().map(|v| v.0).sum::<f32>() / count,
+                    vertices.iter().map(|v| v.1).sum::<f32>() / count,
+                );
+                let reach = vertices
+                    .iter()
+                    .map(|v| ((v.0 - centre.0).powi(2) + (v.1 - centre.1).powi(2)).sqrt())
+                    .fold(0.0, f32::max);
+
+                (centre, reach)
+            }
+        }
+    }
+
+    /// The smallest and largest corners of a box around the shape.
+    pub fn extent(&self, origin: (f32, f32), scale: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+        match self {
+            Shape::Rect { width, height } => (
+                origin,
+                (origin.0 + width * scale.0, origin.1 + height * scale.1),
+            ),
+            Shape::Circle { radius } => {
+                let radius = radius * scale.0;
+
+                (
+                    (origin.0 - radius, origin.1 - radius),
+                    (origin.0 + radius, origin.1 + radius),
+                )
+            }
+            Shape::Polygon { points } => polygon_vertices(points, origin, scale).iter().fold(
+                ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN)),
+                |((min_x, min_y), (max_x, max_y)), (x, y)| {
+                    (
+                        (min_x.min(*x), min_y.min(*y)),
+                        (max_x.max(*x), max_y.max(*y)),
+                    )
+                },
+            ),
+        }
+    }
+}
+
+fn polygon_vertices(
+    points: &[(f32, f32)],
+    origin: (f32, f32),
+    scale: (f32, f32),
+) -> Vec<(f32, f32)> {
+    points
+        .iter()
+        .map(|(px, py)| (origin.0 + px * scale.0, origin.1 + py * scale.1))
+        .collect()
+}
+
+/// The shortest vector from `point` to the line segment between `a` and `b`.
+fn segment_displacement(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> Vector2D {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = abx * abx + aby * aby;
+
+    let t = if length_sq > 0.0 {
+        (((point.0 - a.0) * abx + (point.1 - a.1) * aby) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    Vector2D {
+        x: a.0 + abx * t - point.0,
+        y: a.1 + aby * t - point.1,
+    }
+}
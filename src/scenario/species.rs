@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{body::DeathCause, entity::Entity, occupancy::OccupancyGrid};
+
+use super::{fitness::Fitness, scenario::Scenario};
+
+/// A kind of entity that only breeds with its own kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Species {
+    pub name: String,
+    /// How much of the population belongs to this species, relative to the other species' shares.
+    #[serde(default = "default_share")]
+    pub share: f32,
+    /// Names of the species this one hunts. A hunter kills prey by moving onto or staying on its
+    /// cell, which counts as eating.
+    #[serde(default)]
+    pub preys_on: Vec<String>,
+    /// Used instead of the scenario's fitness to score entities of this species.
+    pub fitness: Option<Fitness>,
+    /// The colour entities of this species are drawn in.
+    pub color: Option<[u8; 4]>,
+}
+
+fn default_share() -> f32 {
+    1.0
+}
+
+/// Splits `total` entities between species by their shares. Rounding leftovers go to the first
+/// species. Without any species, everything goes to the one implicit species.
+pub fn species_counts(species: &[Species], total: u32) -> Vec<u32> {
+    if species.is_empty() {
+        return vec![total];
+    }
+
+    let total_share: f32 = species.iter().map(|s| s.share).sum();
+    let mut counts: Vec<u32> = species
+        .iter()
+        .map(|s| (total as f32 * s.share / total_share).floor() as u32)
+        .collect();
+
+    counts[0] += total - counts.iter().sum::<u32>();
+    counts
+}
+
+/// Moves entities bred under `from` into the matching species of `to`, by name. Entities whose
+/// species `to` doesn't have join its first species.
+pub fn remap_species(entities: &mut [Entity], from: &Scenario, to: &Scenario) {
+    for entity in entities {
+        entity.species = species_named(to, species_name(from, entity));
+    }
+}
+
+/// The name of an entity's species, or `None` in scenarios without species.
+pub fn species_name<'a>(scenario: &'a Scenario, entity: &Entity) -> Option<&'a str> {
+    scenario
+        .species
+        .get(entity.species)
+        .map(|species| species.name.as_str())
+}
+
+/// The index of the species called `name`, or of the first species if there's none by that name.
+pub fn species_named(scenario: &Scenario, name: Option<&str>) -> usize {
+    scenario
+        .species
+        .iter()
+        .position(|species| Some(species.name.as_str()) == name)
+        .unwrap_or(0)
+}
+
+/// Lets hunters catch prey, in entity order. A hunter catches the first living prey standing on
+/// the cell it's about to move onto or stay on. The prey dies where it stands and doesn't move,
+/// and the hunter is fed as if it had eaten food. `occupancy` must have been filled this step,
+/// before anyone moved.
+pub fn resolve_predation(
+    scenario: &Scenario,
+    entities: &mut [Entity],
+    targets: &mut [Option<(u32, u32)>],
+    occupancy: &mut OccupancyGrid,
+) {
+    for hunter in 0..entities.len() {
+        let Some(target) = targets[hunter] else {
+            continue;
+        };
+
+        if !entities[hunter].body.is_alive {
+            continue;
+        }
+
+        let hunter_species = scenario.species_of(&entities[hunter]);
+        let prey = occupancy.residents_at(target).find(|&prey| {
+            entities[prey].body.is_alive
+                && scenario.hunts(hunter_species, scenario.species_of(&entities[prey]))
+        });
+
+        let Some(prey) = prey else {
+            continue;
+        };
+
+        entities[prey].body.die(DeathCause::Predation);
+        occupancy.remove(target);
+        targets[prey] = None;
+
+        let hunter = &mut entities[hunter];
+        hunter.kills += 1;
+
+        if let Some(energy) = &scenario.energy {
+            hunter.body.energy = (hunter.body.energy + energy.food_value).min(energy.max);
+        }
+    }
+}
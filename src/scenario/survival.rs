@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entity::Entity;
+
+use super::region::Region;
+
+/// Where entities have to go to survive the generation, on top of staying alive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Survival {
+    /// Survivors must end the generation inside one of these regions.
+    #[serde(default)]
+    pub safe_zones: Vec<Region>,
+    /// Survivors must have passed through every one of these regions, in order.
+    #[serde(default)]
+    pub checkpoints: Vec<Region>,
+}
+
+impl Survival {
+    /// Moves an entity on to its next checkpoint if it's reached the current one.
+    pub fn visit(&self, entity: &mut Entity) {
+        let position = (entity.body.x, entity.body.y);
+
+        if let Some(checkpoint) = self.checkpoints.get(entity.checkpoints_reached as usize) {
+            if checkpoint.contains(position) {
+                entity.checkpoints_reached += 1;
+            }
+        }
+    }
+
+    pub fn is_met(&self, entity: &Entity) -> bool {
+        let position = (entity.body.x, entity.body.y);
+
+        let in_safe_zone = self.safe_zones.is_empty()
+            || self.safe_zones.iter().any(|zone| zone.contains(position));
+
+        in_safe_zone && entity.checkpoints_reached as usize >= self.checkpoints.len()
+    }
+}
@@ -0,0 +1,94 @@
+pub mod strategy;
+
+use rand::Rng;
+
+use crate::{
+    entity::Entity,
+    entity_config::EntityConfig,
+    scenario::{fitness::Fitness, scenario::Scenario},
+};
+
+fn survival_filter(entity: &Entity, scenario: &Scenario) -> bool {
+    if !scenario.survival.is_met(entity) {
+        return false;
+    }
+
+    if let Some(food) = &scenario.food {
+        let food_selection = if food.cull_for_starvation {
+            entity.times_eaten > 0 || entity.kills > 0
+        } else {
+            true
+        };
+
+        entity.body.is_alive && food_selection
+    } else {
+        entity.body.is_alive
+    }
+}
+
+pub fn select_survivors(scenario: &Scenario, entities: Vec<Entity>) -> Vec<Entity> {
+    entities
+        .into_iter()
+        .filter(|e| survival_filter(e, scenario))
+        .collect()
+}
+
+pub fn calculate_score(entity: &Entity, fitness: &Fitness) -> f32 {
+    let goal_score = match &fitness.goal {
+        Some(goal)
+            if entity.body.is_alive && goal.region.contains((entity.body.x, entity.body.y)) =>
+        {
+            goal.weight
+        }
+        _ => 0.0,
+    };
+
+    metric_score(entity, fitness) + entity.score_offset + goal_score - entity.radiation_penalty
+}
+
+/// Keeps the score an entity earned under the fitness of a phase that's ended, so only what it
+/// does from here on is weighed by the next phase's fitness.
+pub fn bank_phase_score(entity: &mut Entity, ended: &Fitness, next: &Fitness) {
+    entity.score_offset += metric_score(entity, ended) - metric_score(entity, next);
+}
+
+fn metric_score(entity: &Entity, fitness: &Fitness) -> f32 {
+    entity.times_eaten as f32 * fitness.times_eaten
+        + entity.times_irradiated as f32 * fitness.times_irradiated
+        + entity.distance_travelled as f32 * fitness.distance_travelled
+        + entity.steps_alive as f32 * fitness.steps_alive
+        + entity.checkpoints_reached as f32 * fitness.checkpoints_reached
+        + entity.kills as f32 * fitness.kills
+}
+
+/// Picks the survivors that get to breed. Each species is selected separately, by its own fitness.
+pub fn select_breeders(
+    scenario: &Scenario,
+    entity_config: &EntityConfig,
+    survivors: Vec<Entity>,
+    rng: &mut impl Rng,
+) -> Vec<Entity> {
+    let species_counts = scenario.species_counts(entity_config.start_count);
+    let mut pools: Vec<Vec<Entity>> = species_counts.iter().map(|_| Vec::new()).collect();
+
+    for entity in survivors {
+        pools[scenario.species_of(&entity)].push(entity);
+    }
+
+    pools
+        .into_iter()
+        .zip(species_counts)
+        .enumerate()
+        .flat_map(|(species, (survivors, species_count))| {
+            let pool_size = if scenario.limit_population {
+                species_count as usize
+            } else {
+                (survivors.len() as f32 * entity_config.survivor_breed_rate).floor() as usize
+            };
+
+            scenario
+                .selection
+                .select(survivors, scenario.fitness_for(species), pool_size, rng)
+        })
+        .collect()
+}
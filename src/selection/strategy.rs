@@ -0,0 +1,153 @@
+use rand::{seq::index::sample, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{entity::Entity, scenario::fitness::Fitness};
+
+use super::calculate_score;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, tag = "kind", rename_all = "camelCase")]
+pub enum SelectionStrategy {
+    /// The highest-scoring survivors breed.
+    #[default]
+    Truncation,
+    /// Each breeder is the best of `size` survivors drawn at random.
+    Tournament { size: usize },
+    /// Survivors are drawn with a chance proportional to their score.
+    Roulette,
+    /// Survivors are drawn with a chance proportional to their rank by score.
+    Rank,
+    /// The best `eliteFraction` of the pool is kept, and the rest is drawn uniformly at random.
+    ElitismRandom {
+        #[serde(rename = "eliteFraction")]
+        elite_fraction: f32,
+    },
+}
+
+impl SelectionStrategy {
+    /// Picks `pool_size` breeders from `survivors`. No survivor is picked more than once.
+    pub fn select(
+        &self,
+        survivors: Vec<Entity>,
+        fitness: &Fitness,
+        pool_size: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Entity> {
+        let pool_size = pool_size.min(survivors.len());
+        let scores: Vec<f32> = survivors
+            .iter()
+            .map(|entity| calculate_score(entity, fitness))
+            .collect();
+
+        // Survivor indices, best first. Ties keep their original order so results are stable.
+        let mut ranked: Vec<usize> = (0..survivors.len()).collect();
+        ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+
+        let picked = match self {
+            SelectionStrategy::Truncation => ranked.into_iter().take(pool_size).collect(),
+            SelectionStrategy::Tournament { size } => {
+                let mut remaining = ranked;
+                let mut picked = Vec::with_capacity(pool_size);
+
+                for _ in 0..pool_size {
+                    let size = (*size).clamp(1, remaining.len());
+
+                    // `remaining` is sorted best first, so the lowest drawn position wins.
+                    let winner = sample(rng, remaining.len(), size)
+                        .into_iter()
+                        .min()
+                        .unwrap();
+                    picked.push(remaining.remove(winner));
+                }
+
+                picked
+            }
+            SelectionStrategy::Roulette => {
+                // Scale scores to between 0 and 1 so the weighting doesn't depend on how large the
+                // fitness weights are, then lift them so the worst survivor still has a small chance.
+                let min_score = scores.iter().copied().fold(f32::INFINITY, f32::min);
+                let max_score = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let range = (max_score - min_score) as f64;
+                let weights = ranked
+                    .iter()
+                    .map(|i| {
+                        let normalised = if range > 0.0 {
+                            (scores[*i] - min_score) as f64 / range
+                        } else {
+                            0.0
+                        };
+
+                        normalised + ROULETTE_MIN_WEIGHT
+                    })
+                    .collect();
+
+                weighted_sample(ranked, weights, pool_size, rng)
+            }
+            SelectionStrategy::Rank => {
+                let count = ranked.len();
+                let weights = (0..count).map(|rank| (count - rank) as f64).collect();
+
+                weighted_sample(ranked, weights, pool_size, rng)
+            }
+            SelectionStrategy::ElitismRandom { elite_fraction } => {
+                let elite_count =
+                    ((pool_size as f32 * elite_fraction).ceil() as usize).min(pool_size);
+                let mut picked: Vec<usize> = ranked[..elite_count].to_vec();
+
+                let rest = &ranked[elite_count..];
+                picked.extend(
+                    sample(rng, rest.len(), pool_size - elite_count)
+                        .into_iter()
+                        .map(|i| rest[i]),
+                );
+
+                picked
+            }
+        };
+
+        take_indices(survivors, &picked)
+    }
+}
+
+/// Added to every roulette weight, so the lowest-scoring survivor has about a 1% chance of being
+/// picked relative to the highest-scoring one.
+const ROULETTE_MIN_WEIGHT: f64 = 0.01;
+
+/// Draws `count` of `candidates` without replacement, each with a chance proportional to its weight.
+fn weighted_sample(
+    mut candidates: Vec<usize>,
+    mut weights: Vec<f64>,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let mut picked = Vec::with_capacity(count);
+    let mut total: f64 = weights.iter().sum();
+
+    for _ in 0..count {
+        let mut roll = rng.gen::<f64>() * total;
+        let mut chosen = candidates.len() - 1;
+
+        for (i, weight) in weights.iter().enumerate() {
+            if roll < *weight {
+                chosen = i;
+                break;
+            }
+
+            roll -= weight;
+        }
+
+        total -= weights.remove(chosen);
+        picked.push(candidates.remove(chosen));
+    }
+
+    picked
+}
+
+fn take_indices(entities: Vec<Entity>, indices: &[usize]) -> Vec<Entity> {
+    let mut entities: Vec<Option<Entity>> = entities.into_iter().map(Some).collect();
+
+    indices
+        .iter()
+        .map(|i| entities[*i].take().unwrap())
+        .collect()
+}
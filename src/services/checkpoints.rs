@@ -0,0 +1,105 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entity::Entity,
+    rng::SimRng,
+    scenario::{curriculum::Curriculum, scenario::Scenario},
+};
+
+/// Bumped whenever the layout of a checkpoint changes in a way older files can't be read with,
+/// and whenever a neuron kind is added, since that changes which neurons saved genes decode to.
+pub const CHECKPOINT_VERSION: u32 = 8;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointRef<'a> {
+    pub version: u32,
+    pub generation_number: u32,
+    pub current_step: usize,
+    pub entities: &'a [Entity],
+    pub scenario: &'a Scenario,
+    pub curriculum: Option<&'a Curriculum>,
+    pub rng: &'a SimRng,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub generation_number: u32,
+    pub current_step: usize,
+    pub entities: Vec<Entity>,
+    pub scenario: Scenario,
+    pub curriculum: Option<Curriculum>,
+    pub rng: SimRng,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(PathBuf, io::Error),
+    Serde(PathBuf, serde_json::Error),
+    Version(PathBuf, u32),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(path, err) => {
+                write!(f, "could not access checkpoint {}: {}", path.display(), err)
+            }
+            CheckpointError::Serde(path, err) => {
+                write!(f, "invalid checkpoint {}: {}", path.display(), err)
+            }
+            CheckpointError::Version(path, version) => write!(
+                f,
+                "checkpoint {} has version {}, but version {} is required",
+                path.display(),
+                version,
+                CHECKPOINT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+pub fn checkpoint_path(output_dir: &Path, generation_number: u32) -> PathBuf {
+    output_dir.join(format!("checkpoints/{}.json", generation_number))
+}
+
+pub fn save_checkpoint(path: &Path, checkpoint: &CheckpointRef) -> Result<(), CheckpointError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| CheckpointError::Io(path.to_owned(), err))?;
+    }
+
+    let file = fs::File::create(path).map_err(|err| CheckpointError::Io(path.to_owned(), err))?;
+
+    serde_json::to_writer(BufWriter::new(file), checkpoint)
+        .map_err(|err| CheckpointError::Serde(path.to_owned(), err))
+}
+
+#[derive(Deserialize)]
+struct CheckpointVersion {
+    version: u32,
+}
+
+pub fn load_checkpoint(path: &Path) -> Result<Checkpoint, CheckpointError> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| CheckpointError::Io(path.to_owned(), err))?;
+
+    // Check the version on its own first, so an outdated file gets a clear error rather than
+    // whatever field happens to fail to deserialize.
+    let CheckpointVersion { version } = serde_json::from_str(&contents)
+        .map_err(|err| CheckpointError::Serde(path.to_owned(), err))?;
+
+    if version != CHECKPOINT_VERSION {
+        return Err(CheckpointError::Version(path.to_owned(), version));
+    }
+
+    serde_json::from_str(&contents).map_err(|err| CheckpointError::Serde(path.to_owned(), err))
+}
@@ -0,0 +1,104 @@
+use std::{fmt, io, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::scenario::curriculum::Advancement;
+
+use super::{
+    data_path,
+    scenarios::{load_scenario, ScenarioFile, ScenarioLoadError},
+    validation::{validate_curriculum, ScenarioProblem},
+};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageFile {
+    /// Scenario name (looked up in ./data/scenarios) or path to a scenario file.
+    pub scenario: String,
+    /// When to move on to the next stage. Required for every stage but the last.
+    pub until: Option<Advancement>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurriculumFile {
+    #[serde(skip)]
+    pub name: String,
+
+    pub stages: Vec<StageFile>,
+
+    /// The scenario of each stage, loaded along with the curriculum.
+    #[serde(skip)]
+    pub scenarios: Vec<ScenarioFile>,
+}
+
+#[derive(Debug)]
+pub enum CurriculumLoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Invalid(PathBuf, Vec<ScenarioProblem>),
+    Scenario(ScenarioLoadError),
+}
+
+impl fmt::Display for CurriculumLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurriculumLoadError::Io(path, err) => {
+                write!(f, "could not read curriculum {}: {}", path.display(), err)
+            }
+            CurriculumLoadError::Parse(path, err) => {
+                write!(f, "could not parse curriculum {}: {}", path.display(), err)
+            }
+            CurriculumLoadError::Invalid(path, problems) => {
+                write!(f, "invalid curriculum {}:", path.display())?;
+
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+
+                Ok(())
+            }
+            CurriculumLoadError::Scenario(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CurriculumLoadError {}
+
+/// Loads a curriculum file and the scenario of every stage, checking each scenario the same way
+/// `load_scenario` does.
+pub fn load_curriculum(
+    curriculum: &str,
+    grid_width: u32,
+    grid_height: u32,
+    entity_count: u32,
+) -> Result<CurriculumFile, CurriculumLoadError> {
+    let file_path = data_path(curriculum, "curricula");
+
+    let file = std::fs::File::open(&file_path)
+        .map_err(|err| CurriculumLoadError::Io(file_path.clone(), err))?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut curriculum: CurriculumFile = serde_json::from_reader(reader)
+        .map_err(|err| CurriculumLoadError::Parse(file_path.clone(), err))?;
+
+    curriculum.name = file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let problems = validate_curriculum(&curriculum);
+
+    if !problems.is_empty() {
+        return Err(CurriculumLoadError::Invalid(file_path, problems));
+    }
+
+    curriculum.scenarios = curriculum
+        .stages
+        .iter()
+        .map(|stage| load_scenario(&stage.scenario, grid_width, grid_height, entity_count))
+        .collect::<Result<_, _>>()
+        .map_err(CurriculumLoadError::Scenario)?;
+
+    Ok(curriculum)
+}
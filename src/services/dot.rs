@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use dot_writer::{Attributes, DotWriter};
 
 use crate::neural_network::brain::Brain;
@@ -41,8 +43,8 @@ fn neural_net_to_dot(brain: &Brain) -> String {
     std::str::from_utf8(&bytes).unwrap().to_string()
 }
 
-pub fn write_dot_file(brain: &Brain, id: usize) {
+pub fn write_dot_file(output_dir: &Path, brain: &Brain, id: usize) {
     let dot = neural_net_to_dot(brain);
 
-    std::fs::write(format!("./data/dots/{}.dot", id), dot).unwrap();
+    std::fs::write(output_dir.join(format!("dots/{}.dot", id)), dot).unwrap();
 }
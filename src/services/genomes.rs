@@ -0,0 +1,201 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::{
+    genome::gene::Gene,
+    neural_network::{
+        hidden_neuron::HiddenNeuron, input_neuron::InputNeuron, output_neuron::OutputNeuron,
+    },
+};
+
+use super::data_path;
+
+/// Bumped whenever the layout of a genome file changes in a way older files can't be read with.
+pub const GENOME_FILE_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GenomeFormat {
+    /// Human-readable JSON (`.json`).
+    Json,
+    /// Compact bincode encoding (`.bin`).
+    Binary,
+}
+
+impl GenomeFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GenomeFormat::Json => "json",
+            GenomeFormat::Binary => "bin",
+        }
+    }
+
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("bin") => GenomeFormat::Binary,
+            _ => GenomeFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedGenome {
+    pub generation: u32,
+    pub score: f32,
+    pub genome: Vec<Gene>,
+}
+
+/// How many kinds of each neuron there were when a genome file was saved. Genes refer to neurons
+/// by number, so genomes decode to different brains once any of these change.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeuronCounts {
+    pub inputs: u32,
+    pub hidden: u32,
+    pub outputs: u32,
+}
+
+impl NeuronCounts {
+    pub fn current() -> Self {
+        Self {
+            inputs: InputNeuron::COUNT as u32,
+            hidden: HiddenNeuron::COUNT as u32,
+            outputs: OutputNeuron::COUNT as u32,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenomeFile {
+    pub version: u32,
+    pub neurons: NeuronCounts,
+    pub scenario: String,
+    pub genomes: Vec<SavedGenome>,
+}
+
+#[derive(Debug)]
+pub enum GenomeFileError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+    Binary(PathBuf, bincode::Error),
+    Version(PathBuf, u32),
+    Neurons(PathBuf, NeuronCounts),
+    Empty(PathBuf),
+}
+
+impl fmt::Display for GenomeFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeFileError::Io(path, err) => {
+                write!(
+                    f,
+                    "could not access genome file {}: {}",
+                    path.display(),
+                    err
+                )
+            }
+            GenomeFileError::Json(path, err) => {
+                write!(f, "invalid genome file {}: {}", path.display(), err)
+            }
+            GenomeFileError::Binary(path, err) => {
+                write!(f, "invalid genome file {}: {}", path.display(), err)
+            }
+            GenomeFileError::Version(path, version) => write!(
+                f,
+                "genome file {} has version {}, but version {} is required",
+                path.display(),
+                version,
+                GENOME_FILE_VERSION
+            ),
+            GenomeFileError::Neurons(path, saved) => {
+                let current = NeuronCounts::current();
+
+                write!(
+                    f,
+                    "genome file {} was saved with {} input, {} hidden and {} output neurons, but \
+                     there are now {}, {} and {}",
+                    path.display(),
+                    saved.inputs,
+                    saved.hidden,
+                    saved.outputs,
+                    current.inputs,
+                    current.hidden,
+                    current.outputs
+                )
+            }
+            GenomeFileError::Empty(path) => {
+                write!(f, "genome file {} contains no genomes", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenomeFileError {}
+
+pub fn save_genomes(
+    path: &Path,
+    genome_file: &GenomeFile,
+    format: GenomeFormat,
+) -> Result<(), GenomeFileError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| GenomeFileError::Io(path.to_owned(), err))?;
+    }
+
+    let bytes = match format {
+        GenomeFormat::Json => serde_json::to_vec_pretty(genome_file)
+            .map_err(|err| GenomeFileError::Json(path.to_owned(), err))?,
+        GenomeFormat::Binary => bincode::serialize(genome_file)
+            .map_err(|err| GenomeFileError::Binary(path.to_owned(), err))?,
+    };
+
+    fs::write(path, bytes).map_err(|err| GenomeFileError::Io(path.to_owned(), err))
+}
+
+#[derive(Deserialize)]
+struct GenomeFileVersion {
+    version: u32,
+}
+
+pub fn load_genomes(genomes: &str) -> Result<GenomeFile, GenomeFileError> {
+    let path = data_path(genomes, "genomes");
+    let bytes = fs::read(&path).map_err(|err| GenomeFileError::Io(path.clone(), err))?;
+
+    // The version is the first field in both formats, so it can be checked before the rest.
+    let version = match GenomeFormat::from_path(&path) {
+        GenomeFormat::Json => {
+            serde_json::from_slice::<GenomeFileVersion>(&bytes)
+                .map_err(|err| GenomeFileError::Json(path.clone(), err))?
+                .version
+        }
+        GenomeFormat::Binary => bincode::deserialize::<u32>(&bytes)
+            .map_err(|err| GenomeFileError::Binary(path.clone(), err))?,
+    };
+
+    if version != GENOME_FILE_VERSION {
+        return Err(GenomeFileError::Version(path, version));
+    }
+
+    let genome_file: GenomeFile = match GenomeFormat::from_path(&path) {
+        GenomeFormat::Json => serde_json::from_slice(&bytes)
+            .map_err(|err| GenomeFileError::Json(path.clone(), err))?,
+        GenomeFormat::Binary => bincode::deserialize(&bytes)
+            .map_err(|err| GenomeFileError::Binary(path.clone(), err))?,
+    };
+
+    if genome_file.neurons != NeuronCounts::current() {
+        return Err(GenomeFileError::Neurons(path, genome_file.neurons));
+    }
+
+    if genome_file.genomes.is_empty() {
+        return Err(GenomeFileError::Empty(path));
+    }
+
+    Ok(genome_file)
+}
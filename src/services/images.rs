@@ -0,0 +1,182 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// An 8-bit grayscale image, stored row by row.
+#[derive(Debug, Clone)]
+pub struct GrayImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Samples the image as if it were stretched over a `grid_width` by `grid_height` grid.
+    pub fn sample(&self, (x, y): (u32, u32), grid_width: u32, grid_height: u32) -> u8 {
+        let px = (x as u64 * self.width as u64 / grid_width as u64) as usize;
+        let py = (y as u64 * self.height as u64 / grid_height as u64) as usize;
+
+        self.pixels[py * self.width as usize + px]
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageLoadError {
+    Io(PathBuf, io::Error),
+    Png(PathBuf, png::DecodingError),
+    Pgm(PathBuf, &'static str),
+    Unsupported(PathBuf),
+}
+
+impl fmt::Display for ImageLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageLoadError::Io(path, err) => {
+                write!(f, "could not read image {}: {}", path.display(), err)
+            }
+            ImageLoadError::Png(path, err) => {
+                write!(f, "invalid PNG image {}: {}", path.display(), err)
+            }
+            ImageLoadError::Pgm(path, reason) => {
+                write!(f, "invalid PGM image {}: {}", path.display(), reason)
+            }
+            ImageLoadError::Unsupported(path) => {
+                write!(f, "image {} is not a PNG or PGM file", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageLoadError {}
+
+/// Loads a PNG or PGM image, picked by file extension, and converts it to grayscale.
+pub fn load_grayscale(path: &Path) -> Result<GrayImage, ImageLoadError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => load_png(path),
+        Some("pgm") => load_pgm(path),
+        _ => Err(ImageLoadError::Unsupported(path.to_owned())),
+    }
+}
+
+fn load_png(path: &Path) -> Result<GrayImage, ImageLoadError> {
+    let file = File::open(path).map_err(|err| ImageLoadError::Io(path.to_owned(), err))?;
+
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| ImageLoadError::Png(path.to_owned(), err))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| ImageLoadError::Png(path.to_owned(), err))?;
+
+    let channels = info.color_type.samples();
+
+    // Alpha is ignored, and colour is averaged down to a single brightness.
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| match pixel {
+            [gray] | [gray, _] => *gray,
+            [r, g, b, ..] => ((*r as u16 + *g as u16 + *b as u16) / 3) as u8,
+            _ => unreachable!(),
+        })
+        .collect();
+
+    Ok(GrayImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+/// Reads both plain (`P2`) and raw (`P5`) PGM files.
+fn load_pgm(path: &Path) -> Result<GrayImage, ImageLoadError> {
+    let bytes = fs::read(path).map_err(|err| ImageLoadError::Io(path.to_owned(), err))?;
+    let invalid = |reason| ImageLoadError::Pgm(path.to_owned(), reason);
+
+    let mut pos = 0;
+    let mut next_token = |bytes: &[u8]| -> Option<String> {
+        // Skip whitespace and comments, which run to the end of the line.
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'#' => {
+                    while pos < bytes.len() && bytes[pos] != b'\n' {
+                        pos += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        (pos > start).then(|| String::from_utf8_lossy(&bytes[start..pos]).into_owned())
+    };
+
+    let magic = next_token(&bytes).ok_or_else(|| invalid("missing header"))?;
+    let mut header_number = || {
+        next_token(&bytes)
+            .and_then(|token| token.parse::<u32>().ok())
+            .ok_or_else(|| invalid("malformed header"))
+    };
+
+    let width = header_number()?;
+    let height = header_number()?;
+    let max_value = header_number()?;
+
+    if width == 0 || height == 0 {
+        return Err(invalid("image is empty"));
+    }
+
+    if max_value == 0 || max_value > u16::MAX as u32 {
+        return Err(invalid("maximum value out of range"));
+    }
+
+    let count = width as usize * height as usize;
+    let scale = |value: u32| (value.min(max_value) * 255 / max_value) as u8;
+
+    let pixels: Vec<u8> = match magic.as_str() {
+        "P2" => (0..count)
+            .map(|_| {
+                next_token(&bytes)
+                    .and_then(|token| token.parse::<u32>().ok())
+                    .map(scale)
+                    .ok_or_else(|| invalid("not enough pixel values"))
+            })
+            .collect::<Result<_, _>>()?,
+        "P5" => {
+            // A single whitespace byte separates the header from the pixel data.
+            let data = bytes.get(pos + 1..).unwrap_or_default();
+            let sample_size = if max_value < 256 { 1 } else { 2 };
+
+            if data.len() < count * sample_size {
+                return Err(invalid("not enough pixel data"));
+            }
+
+            data.chunks_exact(sample_size)
+                .take(count)
+                .map(|sample| match sample {
+                    [value] => scale(*value as u32),
+                    [high, low] => scale(u16::from_be_bytes([*high, *low]) as u32),
+                    _ => unreachable!(),
+                })
+                .collect()
+        }
+        _ => return Err(invalid("not a P2 or P5 file")),
+    };
+
+    Ok(GrayImage {
+        width,
+        height,
+        pixels,
+    })
+}
@@ -0,0 +1,107 @@
+use std::{fmt, io, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::archipelago::Migration;
+
+use super::{
+    data_path,
+    scenarios::{load_scenario, ScenarioFile, ScenarioLoadError},
+    validation::{validate_islands, ScenarioProblem},
+};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IslandFile {
+    /// Scenario name (looked up in ./data/scenarios) or path to a scenario file.
+    pub scenario: String,
+    /// The island's grid size. Uses the grid from the settings when absent.
+    pub grid: Option<(u32, u32)>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IslandsFile {
+    #[serde(skip)]
+    pub name: String,
+
+    pub migration: Migration,
+    pub islands: Vec<IslandFile>,
+
+    /// The scenario of each island, loaded along with the file.
+    #[serde(skip)]
+    pub scenarios: Vec<ScenarioFile>,
+}
+
+#[derive(Debug)]
+pub enum IslandsLoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Invalid(PathBuf, Vec<ScenarioProblem>),
+    Scenario(ScenarioLoadError),
+}
+
+impl fmt::Display for IslandsLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IslandsLoadError::Io(path, err) => {
+                write!(f, "could not read islands {}: {}", path.display(), err)
+            }
+            IslandsLoadError::Parse(path, err) => {
+                write!(f, "could not parse islands {}: {}", path.display(), err)
+            }
+            IslandsLoadError::Invalid(path, problems) => {
+                write!(f, "invalid islands {}:", path.display())?;
+
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+
+                Ok(())
+            }
+            IslandsLoadError::Scenario(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for IslandsLoadError {}
+
+/// Loads an islands file and the scenario of every island, checking each scenario against the
+/// island's grid the same way `load_scenario` does.
+pub fn load_islands(
+    islands: &str,
+    default_grid: (u32, u32),
+    entity_count: u32,
+) -> Result<IslandsFile, IslandsLoadError> {
+    let file_path = data_path(islands, "islands");
+
+    let file = std::fs::File::open(&file_path)
+        .map_err(|err| IslandsLoadError::Io(file_path.clone(), err))?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut islands: IslandsFile = serde_json::from_reader(reader)
+        .map_err(|err| IslandsLoadError::Parse(file_path.clone(), err))?;
+
+    islands.name = file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let problems = validate_islands(&islands, entity_count);
+
+    if !problems.is_empty() {
+        return Err(IslandsLoadError::Invalid(file_path, problems));
+    }
+
+    islands.scenarios = islands
+        .islands
+        .iter()
+        .map(|island| {
+            let (width, height) = island.grid.unwrap_or(default_grid);
+            load_scenario(&island.scenario, width, height, entity_count)
+        })
+        .collect::<Result<_, _>>()
+        .map_err(IslandsLoadError::Scenario)?;
+
+    Ok(islands)
+}
@@ -1,2 +1,23 @@
+pub mod checkpoints;
+pub mod curricula;
 pub mod dot;
+pub mod genomes;
+pub mod images;
+pub mod islands;
 pub mod scenarios;
+pub mod stats;
+pub mod validation;
+
+use std::path::PathBuf;
+
+/// Resolves a command-line argument naming a data file to a path. Anything that looks like a path
+/// is used as-is, otherwise it's treated as the name of a JSON file in `./data/<dir>`.
+pub fn data_path(arg: &str, dir: &str) -> PathBuf {
+    let path = PathBuf::from(arg);
+
+    if path.extension().is_some() || path.components().count() > 1 {
+        path
+    } else {
+        PathBuf::from(format!("./data/{}/{}.json", dir, arg))
+    }
+}
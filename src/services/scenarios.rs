@@ -1,39 +1,206 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
 use serde::Deserialize;
-use serde_json::{self, Error};
 
-use crate::scenario::radiation_zone::RadiationZone;
+use crate::{
+    genome::crossover::CrossoverStrategy,
+    scenario::{
+        energy::ScenarioEnergy,
+        fitness::Fitness,
+        food::{FoodDistribution, FoodNutrition, FoodRegrowth},
+        movement::MovementPolicy,
+        obstacles::ObstacleMask,
+        radiation_zone::{DoseDeath, DosePenalty, RadiationZone},
+        region::Region,
+        species::Species,
+        survival::Survival,
+    },
+    selection::strategy::SelectionStrategy,
+};
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
+use super::{
+    data_path,
+    images::{load_grayscale, GrayImage, ImageLoadError},
+    validation::{validate_scenario, ScenarioProblem},
+};
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct FoodFile {
+    /// How many cells get food at the start of a generation, at most.
     pub starting_food: u32,
     pub cull_for_starvation: bool,
+    #[serde(default)]
+    pub distribution: FoodDistribution,
+    /// The image for a `mask` distribution, loaded along with the scenario.
+    #[serde(skip)]
+    pub mask: Option<GrayImage>,
+    pub regrowth: Option<FoodRegrowth>,
+    #[serde(default)]
+    pub nutrition: FoodNutrition,
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct RadiationFile {
+    /// Shorthand for a `threshold` death at this dose.
     pub death_threshold: Option<u32>,
+    #[serde(default)]
+    pub recovery: f32,
+    pub death: Option<DoseDeath>,
+    pub penalty: Option<DosePenalty>,
     pub zones: Vec<RadiationZone>,
 }
 
+/// A stretch of the generation with its own food, radiation and fitness. Sections left out are
+/// taken from the top level of the scenario.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseFile {
+    pub steps: usize,
+    pub food: Option<FoodFile>,
+    pub radiation: Option<RadiationFile>,
+    pub fitness: Option<Fitness>,
+}
+
 #[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct ObstaclesFile {
+    #[serde(default)]
+    pub rects: Vec<Region>,
+    pub mask: Option<ObstacleMask>,
+    /// The image for `mask`, loaded along with the scenario.
+    #[serde(skip)]
+    pub mask_image: Option<GrayImage>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct ScenarioFile {
+    #[serde(skip)]
+    pub name: String,
+
     pub supplement_population: bool,
     pub limit_population: bool,
 
+    /// How many steps a generation lasts. Defaults to the total length of `phases`, which it has
+    /// to match if both are given, or without phases, to when the last radiation zone ends.
+    pub generation_steps: Option<usize>,
+
+    /// Ordered stretches of the generation, each with its own food, radiation and fitness.
+    #[serde(default)]
+    pub phases: Vec<PhaseFile>,
+
+    /// Breed children from pairs of parents using this strategy. Children are cloned from a
+    /// single parent when absent.
+    pub crossover: Option<CrossoverStrategy>,
+
+    #[serde(default)]
+    pub selection: SelectionStrategy,
+
+    #[serde(default)]
+    pub fitness: Fitness,
+
+    /// Regions entities have to end up in or pass through to survive.
+    #[serde(default)]
+    pub survival: Survival,
+
+    /// Kinds of entity that breed separately and can hunt each other.
+    #[serde(default)]
+    pub species: Vec<Species>,
+
+    /// What happens when entities try to move onto the same cell. They can stack by default.
+    #[serde(default)]
+    pub movement: MovementPolicy,
+
+    pub energy: Option<ScenarioEnergy>,
     pub food: Option<FoodFile>,
     pub radiation: Option<RadiationFile>,
+    pub obstacles: Option<ObstaclesFile>,
 }
 
-pub fn load_scenario(scenario_name: &str) -> Result<ScenarioFile, Error> {
-    let file_path = format!("./data/scenarios/{}.json", scenario_name);
+#[derive(Debug)]
+pub enum ScenarioLoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Image(ImageLoadError),
+    Invalid(PathBuf, Vec<ScenarioProblem>),
+}
+
+impl fmt::Display for ScenarioLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioLoadError::Io(path, err) => {
+                write!(f, "could not read scenario {}: {}", path.display(), err)
+            }
+            ScenarioLoadError::Parse(path, err) => {
+                write!(f, "could not parse scenario {}: {}", path.display(), err)
+            }
+            ScenarioLoadError::Image(err) => write!(f, "{}", err),
+            ScenarioLoadError::Invalid(path, problems) => {
+                write!(f, "invalid scenario {}:", path.display())?;
+
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
 
-    let file = std::fs::File::open(file_path).unwrap();
+impl std::error::Error for ScenarioLoadError {}
+
+/// Loads a scenario file along with any images it refers to, and checks that it can be run on a
+/// `grid_width` by `grid_height` grid with `entity_count` entities.
+pub fn load_scenario(
+    scenario: &str,
+    grid_width: u32,
+    grid_height: u32,
+    entity_count: u32,
+) -> Result<ScenarioFile, ScenarioLoadError> {
+    let file_path = data_path(scenario, "scenarios");
+
+    let file = std::fs::File::open(&file_path)
+        .map_err(|err| ScenarioLoadError::Io(file_path.clone(), err))?;
     let reader = std::io::BufReader::new(file);
 
-    let scenario = serde_json::from_reader(reader)?;
+    let mut scenario: ScenarioFile = serde_json::from_reader(reader)
+        .map_err(|err| ScenarioLoadError::Parse(file_path.clone(), err))?;
+
+    scenario.name = file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let dir = file_path.parent().unwrap_or(Path::new("."));
+
+    let phase_food = scenario
+        .phases
+        .iter_mut()
+        .filter_map(|phase| phase.food.as_mut());
+    for food in scenario.food.iter_mut().chain(phase_food) {
+        if let FoodDistribution::Mask { path, .. } = &food.distribution {
+            food.mask = Some(load_grayscale(&dir.join(path)).map_err(ScenarioLoadError::Image)?);
+        }
+    }
+
+    if let Some(obstacles) = &mut scenario.obstacles {
+        if let Some(mask) = &obstacles.mask {
+            obstacles.mask_image =
+                Some(load_grayscale(&dir.join(&mask.path)).map_err(ScenarioLoadError::Image)?);
+        }
+    }
+
+    let problems = validate_scenario(&scenario, grid_width, grid_height, entity_count);
+
+    if !problems.is_empty() {
+        return Err(ScenarioLoadError::Invalid(file_path, problems));
+    }
 
     Ok(scenario)
 }
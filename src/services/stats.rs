@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+
+use crate::stats::GenerationStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum StatsFormat {
+    /// One human-readable line per generation.
+    Text,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+enum StatsOutput {
+    Text(Box<dyn Write>),
+    Csv(Box<csv::Writer<Box<dyn Write>>>),
+    Jsonl(Box<dyn Write>),
+}
+
+pub struct StatsWriter {
+    output: StatsOutput,
+}
+
+impl StatsWriter {
+    /// With `append`, records are added to the end of an existing stats file rather than replacing
+    /// it, as when resuming a run, and a CSV header is only written if the file was empty.
+    pub fn new(format: StatsFormat, path: Option<&Path>, append: bool) -> io::Result<Self> {
+        let mut has_header = false;
+
+        let writer: Box<dyn Write> = match path {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                let file = if append {
+                    let file = fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)?;
+                    has_header = file.metadata()?.len() > 0;
+                    file
+                } else {
+                    fs::File::create(path)?
+                };
+
+                Box::new(BufWriter::new(file))
+            }
+            None => Box::new(io::stdout()),
+        };
+
+        let output = match format {
+            StatsFormat::Text => StatsOutput::Text(writer),
+            StatsFormat::Csv => StatsOutput::Csv(Box::new(
+                csv::WriterBuilder::new()
+                    .has_headers(!has_header)
+                    .from_writer(writer),
+            )),
+            StatsFormat::Jsonl => StatsOutput::Jsonl(writer),
+        };
+
+        Ok(Self { output })
+    }
+
+    pub fn write(&mut self, stats: &GenerationStats) -> io::Result<()> {
+        match &mut self.output {
+            StatsOutput::Text(writer) => {
+                if let Some(island) = stats.island {
+                    write!(writer, "Island {}: ", island)?;
+                }
+
+                writeln!(
+                    writer,
+                    "Generation {} over. Breeders {}/{} ({:.2}%)",
+                    stats.generation,
+                    stats.breeders,
+                    stats.population,
+                    stats.breeders as f32 / stats.population as f32 * 100.0
+                )?;
+
+                if let Some(next_scenario) = &stats.next_scenario {
+                    writeln!(
+                        writer,
+                        "Curriculum moving on from {} to {}",
+                        stats.scenario, next_scenario
+                    )?;
+                }
+
+                writer.flush()
+            }
+            StatsOutput::Csv(writer) => {
+                writer.serialize(stats)?;
+                writer.flush()
+            }
+            StatsOutput::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, stats)?;
+                writeln!(writer)?;
+                writer.flush()
+            }
+        }
+    }
+}
@@ -0,0 +1,567 @@
+use std::fmt;
+
+use crate::{
+    scenario::{
+        fitness::Fitness,
+        food::FoodDistribution,
+        obstacles::Obstacles,
+        radiation_zone::{DoseDeath, RadiationZone},
+        region::Region,
+        shape::Shape,
+        species::species_counts,
+    },
+    selection::strategy::SelectionStrategy,
+};
+
+use super::{
+    curricula::CurriculumFile,
+    islands::IslandsFile,
+    scenarios::{FoodFile, RadiationFile, ScenarioFile},
+};
+
+/// Something wrong with a scenario file, and where in the JSON it is.
+#[derive(Debug)]
+pub struct ScenarioProblem {
+    /// E.g. `radiation.zones[2].endTime`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Collects problems as a scenario is checked, so they can all be reported at once.
+#[derive(Default)]
+struct Problems(Vec<ScenarioProblem>);
+
+impl Problems {
+    fn check(&mut self, ok: bool, path: impl Into<String>, message: impl Into<String>) {
+        if !ok {
+            self.0.push(ScenarioProblem {
+                path: path.into(),
+                message: message.into(),
+            });
+        }
+    }
+
+    fn region(&mut self, path: &str, region: &Region, grid_width: u32, grid_height: u32) {
+        if region.width == 0 || region.height == 0 {
+            self.check(false, path, "is empty");
+            return;
+        }
+
+        self.check(
+            region.position.0 as u64 + region.width as u64 <= grid_width as u64
+                && region.position.1 as u64 + region.height as u64 <= grid_height as u64,
+            path,
+            format!("doesn't fit in the {}x{} grid", grid_width, grid_height),
+        );
+    }
+}
+
+/// Checks a loaded scenario against the grid and population it'll be run with. Every problem
+/// found is returned, rather than just the first.
+pub fn validate_scenario(
+    scenario: &ScenarioFile,
+    grid_width: u32,
+    grid_height: u32,
+    entity_count: u32,
+) -> Vec<ScenarioProblem> {
+    let mut problems = Problems::default();
+
+    match &scenario.selection {
+        SelectionStrategy::Tournament { size } => {
+            problems.check(*size >= 1, "selection.size", "must be at least 1");
+        }
+        SelectionStrategy::ElitismRandom { elite_fraction } => {
+            problems.check(
+                (0.0..=1.0).contains(elite_fraction),
+                "selection.eliteFraction",
+                "must be between 0 and 1",
+            );
+        }
+        _ => {}
+    }
+
+    validate_fitness(
+        &mut problems,
+        "fitness",
+        &scenario.fitness,
+        grid_width,
+        grid_height,
+    );
+
+    for (i, zone) in scenario.survival.safe_zones.iter().enumerate() {
+        let path = format!("survival.safeZones[{}]", i);
+        problems.region(&path, zone, grid_width, grid_height);
+    }
+
+    for (i, checkpoint) in scenario.survival.checkpoints.iter().enumerate() {
+        let path = format!("survival.checkpoints[{}]", i);
+        problems.region(&path, checkpoint, grid_width, grid_height);
+    }
+
+    let counts = species_counts(&scenario.species, entity_count);
+
+    for (i, species) in scenario.species.iter().enumerate() {
+        let path = format!("species[{}]", i);
+        problems.check(
+            !scenario.species[..i]
+                .iter()
+                .any(|other| other.name == species.name),
+            format!("{}.name", path),
+            format!("`{}` is used by another species", species.name),
+        );
+        problems.check(
+            species.share > 0.0,
+            format!("{}.share", path),
+            "must be above 0",
+        );
+        problems.check(
+            species.share <= 0.0 || counts[i] > 0,
+            format!("{}.share", path),
+            format!("is too small to get any of {} entities", entity_count),
+        );
+
+        for (j, prey) in species.preys_on.iter().enumerate() {
+            let prey_path = format!("{}.preysOn[{}]", path, j);
+            problems.check(
+                scenario.species.iter().any(|other| &other.name == prey),
+                &prey_path,
+                format!("`{}` isn't one of the scenario's species", prey),
+            );
+            problems.check(
+                &species.name != prey,
+                &prey_path,
+                "a species can't hunt itself",
+            );
+        }
+
+        if let Some(fitness) = &species.fitness {
+            let path = format!("{}.fitness", path);
+            validate_fitness(&mut problems, &path, fitness, grid_width, grid_height);
+        }
+    }
+
+    if let Some(energy) = &scenario.energy {
+        problems.check(energy.starting > 0.0, "energy.starting", "must be above 0");
+        problems.check(
+            energy.max >= energy.starting,
+            "energy.max",
+            "must be at least `starting`",
+        );
+        problems.check(
+            energy.passive_cost >= 0.0,
+            "energy.passiveCost",
+            "can't be negative",
+        );
+        problems.check(
+            energy.move_cost >= 0.0,
+            "energy.moveCost",
+            "can't be negative",
+        );
+        problems.check(
+            energy.food_value >= 0.0,
+            "energy.foodValue",
+            "can't be negative",
+        );
+    }
+
+    if let Some(food) = &scenario.food {
+        validate_food(&mut problems, "food", food, grid_width, grid_height);
+    }
+
+    if let Some(radiation) = &scenario.radiation {
+        validate_radiation(
+            &mut problems,
+            "radiation",
+            radiation,
+            grid_width,
+            grid_height,
+        );
+    }
+
+    for (i, phase) in scenario.phases.iter().enumerate() {
+        let path = format!("phases[{}]", i);
+        problems.check(
+            phase.steps > 0,
+            format!("{}.steps", path),
+            "must be at least 1",
+        );
+
+        if let Some(fitness) = &phase.fitness {
+            let path = format!("{}.fitness", path);
+            validate_fitness(&mut problems, &path, fitness, grid_width, grid_height);
+        }
+
+        if let Some(food) = &phase.food {
+            let path = format!("{}.food", path);
+            validate_food(&mut problems, &path, food, grid_width, grid_height);
+        }
+
+        if let Some(radiation) = &phase.radiation {
+            let path = format!("{}.radiation", path);
+            validate_radiation(&mut problems, &path, radiation, grid_width, grid_height);
+        }
+    }
+
+    let phase_steps: usize = scenario.phases.iter().map(|phase| phase.steps).sum();
+
+    match scenario.generation_steps {
+        Some(steps) if !scenario.phases.is_empty() => problems.check(
+            steps == phase_steps,
+            "generationSteps",
+            format!(
+                "is {} but the phases add up to {} steps; leave it out to use the phases",
+                steps, phase_steps
+            ),
+        ),
+        Some(steps) => problems.check(steps > 0, "generationSteps", "must be at least 1"),
+        None => problems.check(
+            !scenario.phases.is_empty()
+                || scenario
+                    .radiation
+                    .as_ref()
+                    .is_some_and(|radiation| !radiation.zones.is_empty()),
+            "generationSteps",
+            "is required without phases or radiation zones to end the generation",
+        ),
+    }
+
+    if let Some(obstacles) = &scenario.obstacles {
+        for (i, rect) in obstacles.rects.iter().enumerate() {
+            let path = format!("obstacles.rects[{}]", i);
+            problems.region(&path, rect, grid_width, grid_height);
+        }
+    }
+
+    // Entities are spawned on distinct free cells, so there have to be enough of them for the
+    // starting population. Later generations stop growing once the grid is full.
+    let free_cells = match &scenario.obstacles {
+        Some(obstacles) => {
+            let walls = Obstacles::new(
+                &obstacles.rects,
+                obstacles.mask.as_ref().zip(obstacles.mask_image.as_ref()),
+                grid_width,
+                grid_height,
+            );
+
+            (0..(grid_width * grid_height) as usize)
+                .filter(|i| !walls.is_wall_at_index(*i))
+                .count()
+        }
+        None => (grid_width * grid_height) as usize,
+    };
+
+    problems.check(
+        free_cells >= entity_count as usize,
+        if scenario.obstacles.is_some() {
+            "obstacles"
+        } else {
+            "grid"
+        },
+        format!(
+            "leaves {} free cells, too few for {} entities",
+            free_cells, entity_count
+        ),
+    );
+
+    problems.0
+}
+
+/// Checks a curriculum's stages. The scenarios they refer to are checked as they're loaded.
+pub fn validate_curriculum(curriculum: &CurriculumFile) -> Vec<ScenarioProblem> {
+    let mut problems = Problems::default();
+
+    problems.check(
+        !curriculum.stages.is_empty(),
+        "stages",
+        "needs at least one stage",
+    );
+
+    let last = curriculum.stages.len().saturating_sub(1);
+
+    for (i, stage) in curriculum.stages.iter().enumerate() {
+        let path = format!("stages[{}].until", i);
+
+        let Some(until) = &stage.until else {
+            problems.check(i == last, path, "is required for every stage but the last");
+            continue;
+        };
+
+        problems.check(
+            until.generations.is_some() || until.survival_rate.is_some(),
+            &path,
+            "needs `generations` or `survivalRate`",
+        );
+
+        if let Some(generations) = until.generations {
+            problems.check(
+                generations > 0,
+                format!("{}.generations", path),
+                "must be at least 1",
+            );
+        }
+
+        if let Some(survival_rate) = until.survival_rate {
+            problems.check(
+                (0.0..=1.0).contains(&survival_rate),
+                format!("{}.survivalRate", path),
+                "must be between 0 and 1",
+            );
+        }
+    }
+
+    problems.0
+}
+
+/// Checks an islands file. The scenarios it refers to are checked as they're loaded.
+pub fn validate_islands(islands: &IslandsFile, entity_count: u32) -> Vec<ScenarioProblem> {
+    let mut problems = Problems::default();
+
+    problems.check(
+        !islands.islands.is_empty(),
+        "islands",
+        "needs at least one island",
+    );
+    problems.check(
+        islands.migration.interval > 0,
+        "migration.interval",
+        "must be at least 1",
+    );
+    problems.check(
+        islands.migration.migrants <= entity_count,
+        "migration.migrants",
+        format!(
+            "can't be more than the {} entities per island",
+            entity_count
+        ),
+    );
+
+    for (i, island) in islands.islands.iter().enumerate() {
+        if let Some((width, height)) = island.grid {
+            problems.check(
+                width > 0 && height > 0,
+                format!("islands[{}].grid", i),
+                "must be at least 1x1",
+            );
+        }
+    }
+
+    problems.0
+}
+
+fn validate_fitness(
+    problems: &mut Problems,
+    path: &str,
+    fitness: &Fitness,
+    grid_width: u32,
+    grid_height: u32,
+) {
+    if let Some(goal) = &fitness.goal {
+        let path = format!("{}.goal.region", path);
+        problems.region(&path, &goal.region, grid_width, grid_height);
+    }
+}
+
+fn validate_food(
+    problems: &mut Problems,
+    path: &str,
+    food: &FoodFile,
+    grid_width: u32,
+    grid_height: u32,
+) {
+    match &food.distribution {
+        FoodDistribution::Perlin { scale, .. } => {
+            problems.check(
+                *scale > 0.0,
+                format!("{}.distribution.scale", path),
+                "must be above 0",
+            );
+        }
+        FoodDistribution::List { positions } => {
+            for (i, (x, y)) in positions.iter().enumerate() {
+                problems.check(
+                    *x < grid_width && *y < grid_height,
+                    format!("{}.distribution.positions[{}]", path, i),
+                    format!("is outside the {}x{} grid", grid_width, grid_height),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(regrowth) = &food.regrowth {
+        problems.check(
+            (0.0..=1.0).contains(&regrowth.rate),
+            format!("{}.regrowth.rate", path),
+            "must be between 0 and 1",
+        );
+
+        if let Some(season) = &regrowth.season {
+            problems.check(
+                season.period > 0,
+                format!("{}.regrowth.season.period", path),
+                "must be at least 1",
+            );
+            problems.check(
+                season.amplitude >= 0.0,
+                format!("{}.regrowth.season.amplitude", path),
+                "can't be negative",
+            );
+        }
+    }
+
+    // Cells with no nutrition count as having no food.
+    problems.check(
+        food.nutrition.min > 0.0,
+        format!("{}.nutrition.min", path),
+        "must be above 0",
+    );
+    problems.check(
+        food.nutrition.max >= food.nutrition.min,
+        format!("{}.nutrition.max", path),
+        "must be at least `min`",
+    );
+}
+
+fn validate_radiation(
+    problems: &mut Problems,
+    path: &str,
+    radiation: &RadiationFile,
+    grid_width: u32,
+    grid_height: u32,
+) {
+    problems.check(
+        (0.0..=1.0).contains(&radiation.recovery),
+        format!("{}.recovery", path),
+        "must be between 0 and 1",
+    );
+
+    match radiation.death {
+        Some(DoseDeath::Threshold { dose }) => {
+            problems.check(
+                dose >= 0.0,
+                format!("{}.death.dose", path),
+                "can't be negative",
+            );
+        }
+        Some(DoseDeath::Logistic { steepness, .. }) => {
+            problems.check(
+                steepness > 0.0,
+                format!("{}.death.steepness", path),
+                "must be above 0",
+            );
+        }
+        None => {}
+    }
+
+    for (i, zone) in radiation.zones.iter().enumerate() {
+        let path = format!("{}.zones[{}]", path, i);
+        validate_zone(problems, &path, zone, grid_width, grid_height);
+    }
+}
+
+fn validate_zone(
+    problems: &mut Problems,
+    path: &str,
+    zone: &RadiationZone,
+    grid_width: u32,
+    grid_height: u32,
+) {
+    problems.check(
+        zone.start_time <= zone.end_time,
+        format!("{}.endTime", path),
+        "must not be before `startTime`",
+    );
+
+    match &zone.shape {
+        Shape::Rect { width, height } => {
+            problems.check(*width > 0.0, format!("{}.width", path), "must be above 0");
+            problems.check(*height > 0.0, format!("{}.height", path), "must be above 0");
+        }
+        Shape::Circle { radius } => {
+            problems.check(*radius > 0.0, format!("{}.radius", path), "must be above 0");
+        }
+        Shape::Polygon { points } => {
+            problems.check(
+                points.len() >= 3,
+                format!("{}.points", path),
+                "needs at least 3 points",
+            );
+        }
+    }
+
+    problems.check(
+        zone.intensity >= 0.0,
+        format!("{}.intensity", path),
+        "can't be negative",
+    );
+
+    if let Some(edge_intensity) = zone.edge_intensity {
+        problems.check(
+            edge_intensity >= 0.0,
+            format!("{}.edgeIntensity", path),
+            "can't be negative",
+        );
+    }
+
+    for (i, keyframe) in zone.path.iter().enumerate() {
+        let (scale_x, scale_y) = keyframe.scale.axes();
+
+        problems.check(
+            scale_x >= 0.0 && scale_y >= 0.0,
+            format!("{}.path[{}].scale", path, i),
+            "can't be negative",
+        );
+        problems.check(
+            !matches!(zone.shape, Shape::Circle { .. }) || scale_x == scale_y,
+            format!("{}.path[{}].scale", path, i),
+            "must be a single number for a circle",
+        );
+        problems.check(
+            zone.path[..i]
+                .iter()
+                .all(|other| other.time != keyframe.time),
+            format!("{}.path[{}].time", path, i),
+            "is shared with an earlier keyframe",
+        );
+    }
+
+    // The zone moves in straight lines between keyframes and holds still outside them, so it
+    // stays on the grid as long as every keyframe does.
+    let placements: Vec<_> = if zone.path.is_empty() {
+        vec![(format!("{}.position", path), zone.position, (1.0, 1.0))]
+    } else {
+        zone.path
+            .iter()
+            .enumerate()
+            .map(|(i, keyframe)| {
+                (
+                    format!("{}.path[{}]", path, i),
+                    keyframe.position.unwrap_or(zone.position),
+                    keyframe.scale.axes(),
+                )
+            })
+            .collect()
+    };
+
+    for (path, origin, scale) in placements {
+        let ((min_x, min_y), (max_x, max_y)) = zone.shape.extent(origin, scale);
+
+        problems.check(
+            min_x >= 0.0
+                && min_y >= 0.0
+                && max_x <= grid_width as f32
+                && max_y <= grid_height as f32,
+            path,
+            format!(
+                "puts the zone at ({}, {})..({}, {}), outside the {}x{} grid",
+                min_x, min_y, max_x, max_y, grid_width, grid_height
+            ),
+        );
+    }
+}
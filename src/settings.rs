@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use config::{Config, ConfigError};
 use serde::Deserialize;
 
@@ -22,19 +24,67 @@ pub struct Settings {
 
     pub render_rad_zone_color: [u8; 4],
     pub render_background_color: [u8; 4],
+    pub render_wall_color: [u8; 4],
     pub render_pixel_scale: u32,
 
     pub debug: bool,
 }
 
 impl Settings {
-    pub fn new() -> Result<Self, ConfigError> {
-        let s = Config::builder()
-            .add_source(config::File::with_name("default"))
-            // TODO: Find out how to make this optional.
-            // .add_source(config::File::with_name("settings"))
-            .build()?;
-
-        s.try_deserialize()
+    pub fn new(overrides: &[PathBuf]) -> Result<Self, ConfigError> {
+        let mut builder = Config::builder().add_source(config::File::with_name("default"));
+
+        for path in overrides {
+            builder = builder.add_source(config::File::from(path.as_path()));
+        }
+
+        let settings: Self = builder.build()?.try_deserialize()?;
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.entity_start_count == 0 {
+            problems.push("entity_start_count must be greater than 0");
+        }
+
+        if self.entity_survivor_child_count == 0 {
+            problems.push("entity_survivor_child_count must be greater than 0");
+        }
+
+        if !(0.0..=1.0).contains(&self.entity_survivor_breed_rate) {
+            problems.push("entity_survivor_breed_rate must be between 0 and 1");
+        }
+
+        if self.grid_width == 0 || self.grid_height == 0 {
+            problems.push("grid_width and grid_height must be greater than 0");
+        }
+
+        if self.entity_start_count as u64 > self.grid_width as u64 * self.grid_height as u64 {
+            problems.push("entity_start_count must not exceed the number of grid cells");
+        }
+
+        if self.neural_network_hidden_layer_width == 0
+            || self.neural_network_hidden_layer_depth == 0
+        {
+            problems.push("neural network hidden layers must have a width and depth of at least 1");
+        }
+
+        if !(0.0..=1.0).contains(&self.neural_network_mutation_rate) {
+            problems.push("neural_network_mutation_rate must be between 0 and 1");
+        }
+
+        if self.render_pixel_scale == 0 {
+            problems.push("render_pixel_scale must be greater than 0");
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Message(problems.join("; ")))
+        }
     }
 }
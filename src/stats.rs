@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+use crate::{
+    body::DeathCause, entity::Entity, scenario::scenario::Scenario, selection::calculate_score,
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationStats {
+    pub generation: u32,
+    /// Which island the population belongs to, when there are several.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub island: Option<u32>,
+    /// The scenario the generation ran in.
+    pub scenario: String,
+    pub population: u32,
+    pub survivors: u32,
+    pub breeders: u32,
+
+    pub radiation_deaths: u32,
+    pub starvation_deaths: u32,
+    pub predation_deaths: u32,
+
+    pub mean_times_eaten: f32,
+    pub max_times_eaten: u32,
+    pub mean_times_irradiated: f32,
+    pub max_times_irradiated: u32,
+
+    pub score_min: f32,
+    pub score_median: f32,
+    pub score_max: f32,
+    pub score_mean: f32,
+
+    pub mean_genome_length: f32,
+    pub mutations: u32,
+
+    /// The scenario a curriculum moved on to after this generation, if it did.
+    pub next_scenario: Option<String>,
+}
+
+impl GenerationStats {
+    /// Collects stats for a generation that has just ended. Must be called before selection, while
+    /// every entity of the generation is still around. Selection and breeding counts are filled in
+    /// afterwards.
+    pub fn collect(generation: u32, scenario: &Scenario, entities: &[Entity]) -> Self {
+        let population = entities.len() as u32;
+        let count = population.max(1) as f32;
+
+        let cull_for_starvation = scenario
+            .food
+            .as_ref()
+            .is_some_and(|food| food.cull_for_starvation);
+
+        let deaths_from = |cause| {
+            entities
+                .iter()
+                .filter(|e| e.body.death_cause == Some(cause))
+                .count() as u32
+        };
+
+        let radiation_deaths = deaths_from(DeathCause::Radiation);
+        // Entities that ran out of energy during the generation, plus those culled for not eating.
+        let starvation_deaths = deaths_from(DeathCause::Starvation)
+            + if cull_for_starvation {
+                entities
+                    .iter()
+                    .filter(|e| e.body.is_alive && e.times_eaten == 0 && e.kills == 0)
+                    .count() as u32
+            } else {
+                0
+            };
+
+        let mut scores: Vec<f32> = entities
+            .iter()
+            .map(|entity| calculate_score(entity, scenario.fitness_for(entity.species)))
+            .collect();
+        scores.sort_by(f32::total_cmp);
+
+        Self {
+            generation,
+            island: None,
+            scenario: scenario.name.clone(),
+            population,
+            survivors: 0,
+            breeders: 0,
+
+            radiation_deaths,
+            starvation_deaths,
+            predation_deaths: deaths_from(DeathCause::Predation),
+
+            mean_times_eaten: entities.iter().map(|e| e.times_eaten).sum::<u32>() as f32 / count,
+            max_times_eaten: entities.iter().map(|e| e.times_eaten).max().unwrap_or(0),
+            mean_times_irradiated: entities.iter().map(|e| e.times_irradiated).sum::<u32>() as f32
+                / count,
+            max_times_irradiated: entities
+                .iter()
+                .map(|e| e.times_irradiated)
+                .max()
+                .unwrap_or(0),
+
+            score_min: scores.first().copied().unwrap_or(0.0),
+            score_median: scores.get(scores.len() / 2).copied().unwrap_or(0.0),
+            score_max: scores.last().copied().unwrap_or(0.0),
+            score_mean: scores.iter().sum::<f32>() / count,
+
+            mean_genome_length: entities.iter().map(|e| e.brain.genome.len()).sum::<usize>() as f32
+                / count,
+            mutations: 0,
+            next_scenario: None,
+        }
+    }
+}